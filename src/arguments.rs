@@ -1,46 +1,493 @@
-use clap::Parser;
+use std::path::PathBuf;
 
-use crate::config::{
-    BACKGROUND_COLOR, BACKGROUND_COLOR_ACTIVE, BORDER_COLOR, BORDER_SIZE, FONT_COLOR,
-    FONT_COLOR_ACTIVE, FONT_POINT_SIZE, LINE_SPACING, MAX_ITEM_DISPLAY_COUNT,
+use clap::{builder::BoolishValueParser, Parser};
+use serde::{Deserialize, Deserializer};
+
+use crate::{
+    dmenu::{InputFormat, OutputFormat},
+    geometry::{DescriptionPosition, Layout, Length, Position},
+    utils::color_from_str,
 };
 
-#[derive(Parser, Debug)]
+// Every option can be set on the command line, through a `PRACTICALRUNNER_*` environment
+// variable or in the config file, in that order of precedence. Defaults are applied after
+// merging, see `config.rs`. Switches are options too so e.g. `--keep-open=false` can turn off
+// what the config file turns on.
+#[derive(Parser, Deserialize, Debug, Default)]
 #[command(version, about, long_about = None)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
 pub struct Arguments {
-    #[arg(short, long, help = "The menu's prompt message", default_value_t = String::from(""))]
-    pub prompt: String,
+    #[arg(
+        short,
+        long,
+        help = "The menu's prompt message",
+        env = "PRACTICALRUNNER_PROMPT"
+    )]
+    pub prompt: Option<String>,
 
-    #[arg(long, help = "The font to use for the menu.")]
+    #[arg(
+        long,
+        help = "The font to use for the menu.",
+        env = "PRACTICALRUNNER_FONT"
+    )]
     pub font: Option<String>,
 
-    #[arg(long, help = "The default font color", default_value_t = String::from(FONT_COLOR))]
-    pub font_color: String,
+    #[arg(
+        short,
+        long,
+        help = "The color theme, run with --list-themes to see all themes. Individual colors override the theme's",
+        env = "PRACTICALRUNNER_THEME"
+    )]
+    pub theme: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "SOURCE",
+        help = "Import colors from `pywal`, `xresources` or a file (pywal colors.json, base16 .yaml scheme or Xresources), read on every launch. Overrides the theme",
+        env = "PRACTICALRUNNER_COLORS_FROM"
+    )]
+    pub colors_from: Option<String>,
+
+    #[arg(
+        long,
+        help = "The default font color",
+        env = "PRACTICALRUNNER_FONT_COLOR",
+        value_parser = parse_color
+    )]
+    #[serde(deserialize_with = "deserialize_color")]
+    pub font_color: Option<String>,
+
+    #[arg(
+        long,
+        help = "The font color of the active item",
+        env = "PRACTICALRUNNER_FONT_COLOR_ACTIVE",
+        value_parser = parse_color
+    )]
+    #[serde(deserialize_with = "deserialize_color")]
+    pub font_color_active: Option<String>,
+
+    #[arg(
+        long,
+        help = "The font color of matched characters",
+        env = "PRACTICALRUNNER_HIGHLIGHT_COLOR",
+        value_parser = parse_color
+    )]
+    #[serde(deserialize_with = "deserialize_color")]
+    pub highlight_color: Option<String>,
+
+    #[arg(
+        long,
+        help = "The font color of matched characters in the active item",
+        env = "PRACTICALRUNNER_HIGHLIGHT_COLOR_ACTIVE",
+        value_parser = parse_color
+    )]
+    #[serde(deserialize_with = "deserialize_color")]
+    pub highlight_color_active: Option<String>,
+
+    #[arg(
+        long,
+        help = "Draw matched characters in bold",
+        env = "PRACTICALRUNNER_HIGHLIGHT_BOLD",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub highlight_bold: Option<bool>,
+
+    #[arg(
+        long,
+        help = "Underline matched characters",
+        env = "PRACTICALRUNNER_HIGHLIGHT_UNDERLINE",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub highlight_underline: Option<bool>,
+
+    #[arg(
+        long,
+        help = "The font color of descriptions. Defaults to a dimmed font color",
+        env = "PRACTICALRUNNER_DESCRIPTION_COLOR",
+        value_parser = parse_color
+    )]
+    #[serde(deserialize_with = "deserialize_color")]
+    pub description_color: Option<String>,
+
+    #[arg(
+        long,
+        help = "The font color of the active item's description. Defaults to a dimmed active font color",
+        env = "PRACTICALRUNNER_DESCRIPTION_COLOR_ACTIVE",
+        value_parser = parse_color
+    )]
+    #[serde(deserialize_with = "deserialize_color")]
+    pub description_color_active: Option<String>,
+
+    #[arg(
+        long,
+        help = "The window border color",
+        env = "PRACTICALRUNNER_BORDER_COLOR",
+        value_parser = parse_color
+    )]
+    #[serde(deserialize_with = "deserialize_color")]
+    pub border_color: Option<String>,
+
+    #[arg(
+        long,
+        help = "The window border size in pixels",
+        env = "PRACTICALRUNNER_BORDER_SIZE"
+    )]
+    pub border_size: Option<u8>,
 
-    #[arg(long, help = "The font color of the active item", default_value_t = String::from(FONT_COLOR_ACTIVE))]
-    pub font_color_active: String,
+    #[arg(
+        long,
+        help = "The default background color",
+        env = "PRACTICALRUNNER_BACKGROUND_COLOR",
+        value_parser = parse_color
+    )]
+    #[serde(deserialize_with = "deserialize_color")]
+    pub background_color: Option<String>,
 
-    #[arg(long, help = "The window border color", default_value_t = String::from(BORDER_COLOR))]
-    pub border_color: String,
+    #[arg(
+        long,
+        help = "The background color of the active item",
+        env = "PRACTICALRUNNER_BACKGROUND_COLOR_ACTIVE",
+        value_parser = parse_color
+    )]
+    #[serde(deserialize_with = "deserialize_color")]
+    pub background_color_active: Option<String>,
 
-    #[arg(long, help = "The window border size in pixels", default_value_t = BORDER_SIZE)]
-    pub border_size: u8,
+    #[arg(
+        short,
+        long,
+        help = "How to arrange the items",
+        env = "PRACTICALRUNNER_LAYOUT"
+    )]
+    pub layout: Option<Layout>,
 
-    #[arg(long, help = "The default background color", default_value_t = String::from(BACKGROUND_COLOR))]
-    pub background_color: String,
+    #[arg(
+        short,
+        long,
+        help = "The amount of items to display at once",
+        env = "PRACTICALRUNNER_ROWS"
+    )]
+    pub rows: Option<u16>,
 
-    #[arg(long, help = "The background color of the active item", default_value_t = String::from(BACKGROUND_COLOR_ACTIVE))]
-    pub background_color_active: String,
+    #[arg(
+        long,
+        help = "The amount of columns in the grid layout",
+        env = "PRACTICALRUNNER_COLUMNS"
+    )]
+    pub columns: Option<u16>,
 
-    #[arg(short, long, help = "The amount of items to display at once", default_value_t = MAX_ITEM_DISPLAY_COUNT)]
-    pub rows: u16,
+    #[arg(
+        long,
+        help = "The size of icons in pixels",
+        env = "PRACTICALRUNNER_ICON_SIZE"
+    )]
+    pub icon_size: Option<u16>,
 
-    #[arg(long, help = "The menu's font size", default_value_t = FONT_POINT_SIZE)]
-    pub font_size: u16,
+    #[arg(
+        long,
+        help = "Don't show icons next to the items",
+        env = "PRACTICALRUNNER_NO_ICONS",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub no_icons: Option<bool>,
 
-    #[arg(long, help = "The spacing between items", default_value_t = LINE_SPACING)]
-    pub line_spacing: u16,
+    #[arg(
+        long,
+        help = "The icon theme to use. Defaults to the GTK icon theme",
+        env = "PRACTICALRUNNER_ICON_THEME"
+    )]
+    pub icon_theme: Option<String>,
 
-    #[arg(short, long, help = "The index of the target display")]
+    #[arg(long, help = "The menu's font size", env = "PRACTICALRUNNER_FONT_SIZE")]
+    pub font_size: Option<u16>,
+
+    #[arg(
+        long,
+        help = "Where to show the items' descriptions, the horizontal layout never shows them",
+        env = "PRACTICALRUNNER_DESCRIPTION_POSITION"
+    )]
+    pub description_position: Option<DescriptionPosition>,
+
+    #[arg(
+        long,
+        help = "The font size of descriptions. Defaults to three quarters of the font size",
+        env = "PRACTICALRUNNER_DESCRIPTION_FONT_SIZE"
+    )]
+    pub description_font_size: Option<u16>,
+
+    #[arg(
+        long,
+        help = "The spacing between items",
+        env = "PRACTICALRUNNER_LINE_SPACING"
+    )]
+    pub line_spacing: Option<u16>,
+
+    #[arg(
+        short,
+        long,
+        help = "The window width in pixels or as a percentage of the display, e.g. `600` or `40%`",
+        env = "PRACTICALRUNNER_WIDTH"
+    )]
+    pub width: Option<Length>,
+
+    #[arg(
+        long,
+        help = "The space around the input and the items in pixels",
+        env = "PRACTICALRUNNER_PADDING"
+    )]
+    pub padding: Option<u16>,
+
+    #[arg(
+        long,
+        help = "Where to place the window on the display",
+        env = "PRACTICALRUNNER_POSITION"
+    )]
+    pub position: Option<Position>,
+
+    #[arg(
+        long,
+        help = "Move the window right by this many pixels, negative values move it left",
+        env = "PRACTICALRUNNER_X_OFFSET",
+        allow_negative_numbers = true
+    )]
+    pub x_offset: Option<i32>,
+
+    #[arg(
+        long,
+        help = "Move the window down by this many pixels, negative values move it up",
+        env = "PRACTICALRUNNER_Y_OFFSET",
+        allow_negative_numbers = true
+    )]
+    pub y_offset: Option<i32>,
+
+    #[arg(
+        short,
+        long,
+        help = "The index of the target display",
+        env = "PRACTICALRUNNER_DISPLAY"
+    )]
     pub display: Option<u8>,
+
+    #[arg(
+        long,
+        help = "The score bonus for matches at the start of a word",
+        env = "PRACTICALRUNNER_WORD_BOUNDARY_BONUS",
+        allow_negative_numbers = true
+    )]
+    pub word_boundary_bonus: Option<i32>,
+
+    #[arg(
+        long,
+        help = "The score bonus for items starting with the query",
+        env = "PRACTICALRUNNER_PREFIX_BONUS",
+        allow_negative_numbers = true
+    )]
+    pub prefix_bonus: Option<i32>,
+
+    #[arg(
+        long,
+        help = "The score bonus per (decayed) launch from the history",
+        env = "PRACTICALRUNNER_HISTORY_WEIGHT"
+    )]
+    pub history_weight: Option<f64>,
+
+    #[arg(
+        long,
+        help = "Also search subdirectories of the directories in $PATH",
+        env = "PRACTICALRUNNER_RECURSIVE",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub recursive: Option<bool>,
+
+    #[arg(
+        long,
+        help = "Read all directories in $PATH again instead of using the cached list of executables"
+    )]
+    #[serde(skip)]
+    pub rebuild_cache: bool,
+
+    #[arg(
+        long,
+        help = "Don't list applications from desktop entries",
+        env = "PRACTICALRUNNER_NO_DESKTOP_ENTRIES",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub no_desktop_entries: Option<bool>,
+
+    #[arg(
+        long,
+        help = "The command used to run terminal applications, the application's command is appended to it. Defaults to `$TERMINAL -e`",
+        env = "PRACTICALRUNNER_TERMINAL"
+    )]
+    pub terminal: Option<String>,
+
+    #[arg(
+        long,
+        help = "Don't remember launched programs to rank them higher",
+        env = "PRACTICALRUNNER_NO_HISTORY",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub no_history: Option<bool>,
+
+    #[arg(
+        long,
+        help = "The maximum amount of programs to remember",
+        env = "PRACTICALRUNNER_HISTORY_SIZE"
+    )]
+    pub history_size: Option<usize>,
+
+    #[arg(
+        long,
+        help = "Keep the menu open when it loses focus, e.g. by clicking another window",
+        env = "PRACTICALRUNNER_KEEP_OPEN",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub keep_open: Option<bool>,
+
+    #[arg(
+        long,
+        value_name = "KEY=ACTION",
+        help = "Bind a key to an action, e.g. `ctrl+j=next`. Can be used multiple times, the action `none` removes a binding"
+    )]
+    pub bind: Vec<String>,
+
+    #[arg(long, help = "List the available themes and exit")]
+    #[serde(skip)]
+    pub list_themes: bool,
+
+    #[arg(long, help = "List the actions that keys can be bound to and exit")]
+    #[serde(skip)]
+    pub list_actions: bool,
+
+    #[arg(
+        long,
+        help = "How to read the items on stdin in dmenu mode",
+        env = "PRACTICALRUNNER_INPUT_FORMAT"
+    )]
+    pub input_format: Option<InputFormat>,
+
+    #[arg(
+        long,
+        help = "How to print the selection in dmenu mode",
+        env = "PRACTICALRUNNER_OUTPUT_FORMAT"
+    )]
+    pub output_format: Option<OutputFormat>,
+
+    #[arg(
+        long,
+        help = "Read items from stdin and print the selection to stdout instead of running it. Enabled automatically when stdin is a pipe or file"
+    )]
+    #[serde(skip)]
+    pub dmenu: bool,
+
+    #[arg(
+        long,
+        help = "The config file to use. Defaults to `$XDG_CONFIG_HOME/practicalrunner/config.toml`",
+        env = "PRACTICALRUNNER_CONFIG"
+    )]
+    #[serde(skip)]
+    pub config: Option<PathBuf>,
+}
+
+impl Arguments {
+    // Fills in every option that isn't set with the one from `other`
+    pub fn merge(self, other: Arguments) -> Self {
+        Self {
+            prompt: self.prompt.or(other.prompt),
+            font: self.font.or(other.font),
+            theme: self.theme.or(other.theme),
+            colors_from: self.colors_from.or(other.colors_from),
+            font_color: self.font_color.or(other.font_color),
+            font_color_active: self.font_color_active.or(other.font_color_active),
+            highlight_color: self.highlight_color.or(other.highlight_color),
+            highlight_color_active: self.highlight_color_active.or(other.highlight_color_active),
+            highlight_bold: self.highlight_bold.or(other.highlight_bold),
+            highlight_underline: self.highlight_underline.or(other.highlight_underline),
+            description_color: self.description_color.or(other.description_color),
+            description_color_active: self
+                .description_color_active
+                .or(other.description_color_active),
+            border_color: self.border_color.or(other.border_color),
+            border_size: self.border_size.or(other.border_size),
+            background_color: self.background_color.or(other.background_color),
+            background_color_active: self
+                .background_color_active
+                .or(other.background_color_active),
+            layout: self.layout.or(other.layout),
+            rows: self.rows.or(other.rows),
+            columns: self.columns.or(other.columns),
+            icon_size: self.icon_size.or(other.icon_size),
+            no_icons: self.no_icons.or(other.no_icons),
+            icon_theme: self.icon_theme.or(other.icon_theme),
+            font_size: self.font_size.or(other.font_size),
+            description_position: self.description_position.or(other.description_position),
+            description_font_size: self.description_font_size.or(other.description_font_size),
+            line_spacing: self.line_spacing.or(other.line_spacing),
+            width: self.width.or(other.width),
+            padding: self.padding.or(other.padding),
+            position: self.position.or(other.position),
+            x_offset: self.x_offset.or(other.x_offset),
+            y_offset: self.y_offset.or(other.y_offset),
+            display: self.display.or(other.display),
+            word_boundary_bonus: self.word_boundary_bonus.or(other.word_boundary_bonus),
+            prefix_bonus: self.prefix_bonus.or(other.prefix_bonus),
+            history_weight: self.history_weight.or(other.history_weight),
+            recursive: self.recursive.or(other.recursive),
+            rebuild_cache: self.rebuild_cache || other.rebuild_cache,
+            no_desktop_entries: self.no_desktop_entries.or(other.no_desktop_entries),
+            terminal: self.terminal.or(other.terminal),
+            no_history: self.no_history.or(other.no_history),
+            history_size: self.history_size.or(other.history_size),
+            keep_open: self.keep_open.or(other.keep_open),
+            // bindings from the config file come first so the command line ones replace them
+            bind: [other.bind, self.bind].concat(),
+            list_themes: self.list_themes || other.list_themes,
+            list_actions: self.list_actions || other.list_actions,
+            input_format: self.input_format.or(other.input_format),
+            output_format: self.output_format.or(other.output_format),
+            dmenu: self.dmenu || other.dmenu,
+            config: self.config.or(other.config),
+        }
+    }
+}
+
+fn parse_color(value: &str) -> Result<String, String> {
+    match color_from_str(value) {
+        Some(_) => Ok(value.to_string()),
+        None => Err(format!(
+            "invalid color `{}`, expected e.g. `#rrggbb`, `#rrggbbaa`, `rgb(r, g, b)`, `hsl(h, s%, l%)` or a color name",
+            value
+        )),
+    }
+}
+
+pub fn deserialize_color<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+
+    parse_color(&value)
+        .map(Some)
+        .map_err(serde::de::Error::custom)
 }
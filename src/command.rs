@@ -0,0 +1,297 @@
+use std::{env, process};
+
+const SHELL_OPERATORS: &[char] = &['|', '&', ';', '<', '>', '(', ')', '`'];
+const GLOB_CHARACTERS: &[char] = &['*', '?', '['];
+
+pub enum CommandLine {
+    Words(Vec<String>),
+    Shell(String),
+}
+
+impl CommandLine {
+    pub fn parse(line: &str) -> Self {
+        match split_words(line) {
+            Some(words) if !words.is_empty() => Self::Words(words),
+            _ => Self::Shell(line.to_string()),
+        }
+    }
+
+    pub fn into_command(self) -> process::Command {
+        match self {
+            Self::Words(words) => {
+                let mut command = process::Command::new(&words[0]);
+                command.args(&words[1..]);
+                command
+            }
+            Self::Shell(line) => {
+                let shell = env::var("SHELL").unwrap_or_else(|_| String::from("/bin/sh"));
+
+                let mut command = process::Command::new(shell);
+                command.arg("-c").arg(line);
+                command
+            }
+        }
+    }
+}
+
+// Splits a line into words using shell quoting rules. Returns `None` if the line needs an
+// actual shell, i.e. it contains unquoted operators, globs, comments, variables (which would be
+// split into words), command substitution, a leading variable assignment or unbalanced quotes.
+pub fn split_words(line: &str) -> Option<Vec<String>> {
+    let mut words: Vec<String> = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    // whether the word so far is only unquoted characters, which makes `NAME=` an assignment
+    let mut unquoted = true;
+
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                    unquoted = true;
+                }
+            }
+            c if SHELL_OPERATORS.contains(&c) || GLOB_CHARACTERS.contains(&c) => return None,
+            '#' if !in_word => return None,
+            '=' if words.is_empty() && unquoted && is_variable_name(&word) => return None,
+            '\\' => {
+                match chars.next() {
+                    // a backslash-newline is a line continuation
+                    Some('\n') => (),
+                    Some(escaped) => word.push(escaped),
+                    None => return None,
+                }
+                in_word = true;
+                unquoted = false;
+            }
+            '\'' => {
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.push(c),
+                        None => return None,
+                    }
+                }
+                in_word = true;
+                unquoted = false;
+            }
+            '"' => {
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(escaped @ ('$' | '`' | '"' | '\\')) => word.push(escaped),
+                            Some('\n') => (),
+                            Some(other) => {
+                                word.push('\\');
+                                word.push(other);
+                            }
+                            None => return None,
+                        },
+                        Some('`') => return None,
+                        Some('$') => word.push_str(&expand_variable(&mut chars)?),
+                        Some(c) => word.push(c),
+                        None => return None,
+                    }
+                }
+                in_word = true;
+                unquoted = false;
+            }
+            // unquoted variables are split into words and globbed by the shell, a `$` that
+            // doesn't start an expansion is literal
+            '$' => {
+                if chars
+                    .peek()
+                    .is_some_and(|next| next.is_ascii_alphanumeric() || "{(_?!#@*-".contains(*next))
+                {
+                    return None;
+                }
+                word.push('$');
+                in_word = true;
+            }
+            '~' if !in_word => {
+                // leave `~user` to the shell
+                if chars
+                    .peek()
+                    .is_some_and(|next| *next != '/' && !next.is_whitespace())
+                {
+                    return None;
+                }
+
+                match home::home_dir() {
+                    Some(home) => word.push_str(&home.to_string_lossy()),
+                    None => word.push('~'),
+                }
+                in_word = true;
+                unquoted = false;
+            }
+            c => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+
+    if in_word {
+        words.push(word);
+    }
+
+    Some(words)
+}
+
+fn is_variable_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+// Expands the variable following a `$`. Returns `None` for anything only a shell can expand,
+// like `$(...)` or `${VAR:-default}`.
+fn expand_variable(chars: &mut std::iter::Peekable<std::str::Chars>) -> Option<String> {
+    let mut name = String::new();
+
+    match chars.peek() {
+        Some('{') => {
+            chars.next();
+            loop {
+                match chars.next() {
+                    Some('}') => break,
+                    Some(c) if c.is_ascii_alphanumeric() || c == '_' => name.push(c),
+                    _ => return None,
+                }
+            }
+        }
+        Some('(') => return None,
+        Some(c) if c.is_ascii_alphabetic() || *c == '_' => {
+            while let Some(&c) = chars.peek() {
+                if !(c.is_ascii_alphanumeric() || c == '_') {
+                    break;
+                }
+                name.push(c);
+                chars.next();
+            }
+        }
+        // special parameters like `$?` or `$1`
+        Some(c) if c.is_ascii_digit() || "?!#@*-".contains(*c) => return None,
+        _ => return Some(String::from("$")),
+    }
+
+    Some(env::var(name).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(words: &[&str]) -> Option<Vec<String>> {
+        Some(words.iter().map(|word| word.to_string()).collect())
+    }
+
+    #[test]
+    fn splits_on_whitespace() {
+        assert_eq!(split_words("  ls   -la  "), strings(&["ls", "-la"]));
+        assert_eq!(split_words(""), strings(&[]));
+    }
+
+    #[test]
+    fn keeps_quoted_text_together() {
+        assert_eq!(
+            split_words(r#"echo 'a b' "c d" e\ f"#),
+            strings(&["echo", "a b", "c d", "e f"])
+        );
+        assert_eq!(
+            split_words(r#"echo "a\"b\$c\n""#),
+            strings(&["echo", r#"a"b$c\n"#])
+        );
+        assert_eq!(
+            split_words(r#"echo '$HOME' '*'"#),
+            strings(&["echo", "$HOME", "*"])
+        );
+        assert_eq!(split_words("echo 'a'b\"c\""), strings(&["echo", "abc"]));
+    }
+
+    #[test]
+    fn rejects_unbalanced_quotes() {
+        assert_eq!(split_words("echo 'abc"), None);
+        assert_eq!(split_words("echo \"abc"), None);
+        assert_eq!(split_words("echo abc\\"), None);
+    }
+
+    #[test]
+    fn expands_home() {
+        let home = home::home_dir().unwrap().to_string_lossy().into_owned();
+
+        assert_eq!(split_words("ls ~"), strings(&["ls", &home]));
+        assert_eq!(
+            split_words("ls ~/bin"),
+            strings(&["ls", &format!("{}/bin", home)])
+        );
+        assert_eq!(split_words("echo a~b"), strings(&["echo", "a~b"]));
+    }
+
+    #[test]
+    fn expands_quoted_variables() {
+        env::set_var("PRACTICALRUNNER_TEST_WORDS", "a b");
+
+        assert_eq!(
+            split_words("echo \"$PRACTICALRUNNER_TEST_WORDS\" \"${PRACTICALRUNNER_TEST_WORDS}c\""),
+            strings(&["echo", "a b", "a bc"])
+        );
+        assert_eq!(
+            split_words("echo \"$PRACTICALRUNNER_TEST_UNSET\""),
+            strings(&["echo", ""])
+        );
+        assert_eq!(split_words("echo $ \"$\""), strings(&["echo", "$", "$"]));
+    }
+
+    #[test]
+    fn leaves_shell_syntax_to_the_shell() {
+        for line in [
+            "ls | less",
+            "make && make install",
+            "echo a > file",
+            "echo $(date)",
+            "echo `date`",
+            "echo \"$(date)\"",
+            "echo \"${A:-b}\"",
+            "echo $1",
+            "ls *.txt",
+            "ls file?",
+            "ls [ab]",
+            "FOO=1 env",
+            "cmd # comment",
+            "$EDITOR file",
+            "echo ${HOME}",
+            "ls ~user",
+        ] {
+            assert_eq!(split_words(line), None, "{}", line);
+        }
+    }
+
+    #[test]
+    fn only_treats_leading_unquoted_names_as_assignments() {
+        assert_eq!(
+            split_words("env FOO=1 cmd"),
+            strings(&["env", "FOO=1", "cmd"])
+        );
+        assert_eq!(split_words("'FOO=1' cmd"), strings(&["FOO=1", "cmd"]));
+        assert_eq!(split_words("--foo=1"), strings(&["--foo=1"]));
+        assert_eq!(split_words("echo a#b"), strings(&["echo", "a#b"]));
+    }
+
+    #[test]
+    fn falls_back_to_the_shell() {
+        assert!(matches!(
+            CommandLine::parse("firefox --new-window"),
+            CommandLine::Words(words) if words == ["firefox", "--new-window"]
+        ));
+        assert!(matches!(
+            CommandLine::parse("ls *.txt"),
+            CommandLine::Shell(line) if line == "ls *.txt"
+        ));
+        assert!(matches!(CommandLine::parse("  "), CommandLine::Shell(_)));
+    }
+}
@@ -1,27 +1,107 @@
+use crate::{
+    geometry::{DescriptionPosition, Layout, Length, Position},
+    keymap::Keymap,
+};
+
 pub const MAX_ITEM_DISPLAY_COUNT: u16 = 9;
+pub const GRID_COLUMNS: u16 = 4;
+pub const GRID_ROWS: u16 = 3;
+pub const ICON_SIZE: u16 = 48;
 pub const PADDING: u16 = 8;
+pub const WINDOW_WIDTH: Length = Length::Pixels(480);
 pub const LINE_SPACING: u16 = 2;
 pub const FONT_POINT_SIZE: u16 = 16;
 
+// The default colors are the `catppuccin-mocha` theme, also used for colors a theme leaves out
 pub const FONT_COLOR: &str = "#cdd6f4";
 pub const FONT_COLOR_ACTIVE: &str = "#1e1e2e";
 
+pub const HIGHLIGHT_COLOR: &str = "#f9e2af";
+pub const HIGHLIGHT_COLOR_ACTIVE: &str = "#f5e0dc";
+
 pub const BACKGROUND_COLOR: &str = "#1e1e2e";
 pub const BACKGROUND_COLOR_ACTIVE: &str = "#89b4fa";
 
 pub const BORDER_SIZE: u8 = 1;
 pub const BORDER_COLOR: &str = "#585b70";
 
+pub const WORD_BOUNDARY_BONUS: i32 = 8;
+pub const PREFIX_BONUS: i32 = 16;
+pub const HISTORY_WEIGHT: f64 = 8.0;
+
+pub const KEYBINDINGS: &[(&str, &str)] = &[
+    ("Return", "accept"),
+    ("shift+Return", "accept-input"),
+    ("Escape", "cancel"),
+    ("ctrl+c", "cancel"),
+    ("Down", "next"),
+    ("ctrl+n", "next"),
+    ("Up", "prev"),
+    ("ctrl+p", "prev"),
+    ("PageDown", "page-down"),
+    ("PageUp", "page-up"),
+    ("ctrl+Home", "first"),
+    ("ctrl+End", "last"),
+    ("Tab", "complete"),
+    ("Left", "cursor-left"),
+    ("ctrl+b", "cursor-left"),
+    ("Right", "cursor-right"),
+    ("ctrl+f", "cursor-right"),
+    ("ctrl+Left", "word-left"),
+    ("alt+b", "word-left"),
+    ("ctrl+Right", "word-right"),
+    ("alt+f", "word-right"),
+    ("Home", "line-start"),
+    ("ctrl+a", "line-start"),
+    ("End", "line-end"),
+    ("ctrl+e", "line-end"),
+    ("Backspace", "delete-char-backward"),
+    ("Delete", "delete-char"),
+    ("ctrl+w", "delete-word"),
+    ("alt+Backspace", "delete-word-alphanumeric"),
+    ("ctrl+u", "kill-to-start"),
+    ("ctrl+k", "kill-to-end"),
+    ("ctrl+y", "yank"),
+];
+
+pub const TERMINAL: &str = "xterm -e";
+
+pub const HISTORY_SIZE: usize = 1000;
+
 pub struct RunnerMenuSettings {
     pub font_color: String,
     pub font_color_active: String,
+    pub highlight_color: String,
+    pub highlight_color_active: String,
+    pub highlight_bold: bool,
+    pub highlight_underline: bool,
+    // dimmed font colors if not set
+    pub description_color: Option<String>,
+    pub description_color_active: Option<String>,
     pub border_color: String,
     pub border_size: u8,
     pub background_color: String,
     pub background_color_active: String,
+    pub layout: Layout,
     pub rows: u16,
+    pub columns: u16,
+    pub icon_size: u16,
+    pub icons: bool,
+    pub icon_theme: Option<String>,
     pub font_size: u16,
+    pub description_position: DescriptionPosition,
+    pub description_font_size: u16,
     pub line_spacing: u16,
     pub display_index: Option<u8>,
+    pub width: Length,
+    pub padding: u16,
+    pub position: Position,
+    pub offset: (i32, i32),
     pub font: Option<String>,
+    pub word_boundary_bonus: i32,
+    pub prefix_bonus: i32,
+    pub history_weight: f64,
+    pub keymap: Keymap,
+    pub keep_open: bool,
+    pub sort_items: bool,
 }
@@ -0,0 +1,31 @@
+use std::{fs, io, path::PathBuf};
+
+use crate::{arguments::Arguments, utils::xdg_dir};
+
+pub fn get_config_dir() -> Option<PathBuf> {
+    Some(xdg_dir("XDG_CONFIG_HOME", ".config")?.join("practicalrunner"))
+}
+
+pub fn get_config_path() -> Option<PathBuf> {
+    Some(get_config_dir()?.join("config.toml"))
+}
+
+// Reads the options from the config file. A missing file is only an error if its path was
+// given explicitly.
+pub fn read_config_file(path: Option<PathBuf>) -> Result<Arguments, String> {
+    let explicit = path.is_some();
+
+    let Some(path) = path.or_else(get_config_path) else {
+        return Ok(Arguments::default());
+    };
+
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound && !explicit => {
+            return Ok(Arguments::default())
+        }
+        Err(e) => return Err(format!("Could not read {}: {}", path.display(), e)),
+    };
+
+    toml::from_str(&content).map_err(|e| format!("Invalid config file {}: {}", path.display(), e))
+}
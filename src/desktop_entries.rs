@@ -0,0 +1,459 @@
+use std::{
+    collections::HashSet,
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    command::split_words,
+    item::{Item, Source, DESKTOP_FILE_KEY},
+    utils::{is_executable, xdg_dir},
+};
+
+const DEFAULT_DATA_DIRS: &str = "/usr/local/share:/usr/share";
+
+#[derive(Debug, Clone)]
+pub struct DesktopEntry {
+    pub name: String,
+    pub generic_name: Option<String>,
+    pub comment: Option<String>,
+    pub keywords: Vec<String>,
+    pub exec: String,
+    pub icon: Option<String>,
+    pub terminal: bool,
+    pub working_directory: Option<String>,
+    pub file_path: PathBuf,
+}
+
+impl DesktopEntry {
+    // The entry as shown in the menu, the desktop file is used to launch it
+    pub fn item(&self) -> Item {
+        Item {
+            label: self.name.clone(),
+            value: self.exec.clone(),
+            description: self.generic_name.clone().or_else(|| self.comment.clone()),
+            icon: self.icon.clone(),
+            keywords: self.keywords.clone(),
+            source: Source::DesktopEntry,
+            metadata: [(
+                DESKTOP_FILE_KEY.to_string(),
+                self.file_path.to_string_lossy().into(),
+            )]
+            .into_iter()
+            .collect(),
+        }
+    }
+
+    // Builds the argument list from the Exec key. Field codes for files and URLs are dropped
+    // since we never launch an entry with any.
+    pub fn command(&self, terminal_command: &str) -> Option<Vec<String>> {
+        let mut words: Vec<String> = Vec::new();
+
+        if self.terminal {
+            words.extend(split_words(terminal_command)?);
+        }
+
+        for argument in split_exec(&self.exec)? {
+            match argument.as_str() {
+                "%f" | "%F" | "%u" | "%U" | "%d" | "%D" | "%n" | "%N" | "%v" | "%m" => (),
+                "%i" => {
+                    if let Some(ref icon) = self.icon {
+                        words.push(String::from("--icon"));
+                        words.push(icon.clone());
+                    }
+                }
+                _ => words.push(self.expand_field_codes(&argument)),
+            }
+        }
+
+        if words.is_empty() {
+            None
+        } else {
+            Some(words)
+        }
+    }
+
+    fn expand_field_codes(&self, argument: &str) -> String {
+        let mut expanded = String::new();
+        let mut chars = argument.chars();
+
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                expanded.push(c);
+                continue;
+            }
+
+            match chars.next() {
+                Some('%') => expanded.push('%'),
+                Some('c') => expanded.push_str(&self.name),
+                Some('k') => expanded.push_str(&self.file_path.to_string_lossy()),
+                _ => (),
+            }
+        }
+
+        expanded
+    }
+}
+
+pub fn get_desktop_entries() -> Vec<DesktopEntry> {
+    let mut entries: Vec<DesktopEntry> = Vec::new();
+    // entries in directories with a higher priority shadow entries with the same id,
+    // even if they are hidden
+    let mut ids: HashSet<String> = HashSet::new();
+
+    let current_desktops: Vec<String> = env::var("XDG_CURRENT_DESKTOP")
+        .unwrap_or_default()
+        .split(':')
+        .filter(|desktop| !desktop.is_empty())
+        .map(String::from)
+        .collect();
+
+    for directory in get_application_dirs() {
+        let mut files: Vec<(String, PathBuf)> = Vec::new();
+        get_desktop_files(&directory, "", &mut files);
+
+        for (id, path) in files {
+            if !ids.insert(id) {
+                continue;
+            }
+
+            if let Some(entry) = parse_desktop_file(&path, &current_desktops) {
+                entries.push(entry);
+            }
+        }
+    }
+
+    entries
+}
+
+fn get_application_dirs() -> Vec<PathBuf> {
+    get_data_dirs()
+        .into_iter()
+        .map(|directory| directory.join("applications"))
+        .collect()
+}
+
+// $XDG_DATA_HOME followed by $XDG_DATA_DIRS, in order of preference
+pub fn get_data_dirs() -> Vec<PathBuf> {
+    let mut directories: Vec<PathBuf> = Vec::new();
+
+    directories.extend(xdg_dir("XDG_DATA_HOME", ".local/share"));
+
+    let data_dirs = env::var_os("XDG_DATA_DIRS")
+        .filter(|data_dirs| !data_dirs.is_empty())
+        .unwrap_or_else(|| DEFAULT_DATA_DIRS.into());
+    directories.extend(env::split_paths(&data_dirs));
+
+    directories
+}
+
+// Collects the desktop files below a directory along with their desktop file ids, which are
+// the relative paths with `/` replaced by `-`
+fn get_desktop_files(path: &Path, prefix: &str, files: &mut Vec<(String, PathBuf)>) {
+    let Ok(dirs) = fs::read_dir(path) else {
+        return;
+    };
+
+    for entry in dirs.flatten() {
+        let Ok(file_name) = entry.file_name().into_string() else {
+            continue;
+        };
+        let entry_path = entry.path();
+
+        if entry_path.is_dir() {
+            // symlinked directories could loop
+            if !entry.file_type().is_ok_and(|t| t.is_symlink()) {
+                get_desktop_files(&entry_path, &format!("{}{}-", prefix, file_name), files);
+            }
+        } else if file_name.ends_with(".desktop") {
+            files.push((format!("{}{}", prefix, file_name), entry_path));
+        }
+    }
+}
+
+fn parse_desktop_file(path: &Path, current_desktops: &[String]) -> Option<DesktopEntry> {
+    let content = fs::read_to_string(path).ok()?;
+
+    let mut in_desktop_entry = false;
+
+    let mut entry_type: Option<String> = None;
+    let mut name: Option<String> = None;
+    let mut generic_name: Option<String> = None;
+    let mut comment: Option<String> = None;
+    let mut keywords: Vec<String> = Vec::new();
+    let mut exec: Option<String> = None;
+    let mut try_exec: Option<String> = None;
+    let mut icon: Option<String> = None;
+    let mut working_directory: Option<String> = None;
+    let mut terminal = false;
+    let mut only_show_in: Option<String> = None;
+    let mut not_show_in: Option<String> = None;
+
+    for line in content.lines() {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if line.starts_with('[') {
+            // only the first group can be the main group
+            if in_desktop_entry {
+                break;
+            }
+            in_desktop_entry = line == "[Desktop Entry]";
+            continue;
+        }
+
+        if !in_desktop_entry {
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = unescape_value(value.trim());
+
+        match key.trim() {
+            "Type" => entry_type = Some(value),
+            "Name" => name = Some(value),
+            "GenericName" => generic_name = Some(value),
+            "Comment" => comment = Some(value),
+            "Keywords" => {
+                keywords = value
+                    .split(';')
+                    .filter(|keyword| !keyword.is_empty())
+                    .map(String::from)
+                    .collect()
+            }
+            "Exec" => exec = Some(value),
+            "TryExec" => try_exec = Some(value),
+            "Icon" => icon = Some(value),
+            "Path" => working_directory = Some(value),
+            "Terminal" => terminal = value == "true",
+            "NoDisplay" | "Hidden" if value == "true" => return None,
+            "OnlyShowIn" => only_show_in = Some(value),
+            "NotShowIn" => not_show_in = Some(value),
+            _ => (),
+        }
+    }
+
+    if entry_type.as_deref() != Some("Application") {
+        return None;
+    }
+
+    if let Some(only_show_in) = only_show_in {
+        if !current_desktops
+            .iter()
+            .any(|desktop| list_contains(&only_show_in, desktop))
+        {
+            return None;
+        }
+    }
+
+    if let Some(not_show_in) = not_show_in {
+        if current_desktops
+            .iter()
+            .any(|desktop| list_contains(&not_show_in, desktop))
+        {
+            return None;
+        }
+    }
+
+    if let Some(try_exec) = try_exec {
+        if !is_executable_available(&try_exec) {
+            return None;
+        }
+    }
+
+    Some(DesktopEntry {
+        name: name?,
+        generic_name: generic_name.filter(|generic_name| !generic_name.is_empty()),
+        comment: comment.filter(|comment| !comment.is_empty()),
+        keywords,
+        exec: exec?,
+        icon: icon.filter(|icon| !icon.is_empty()),
+        terminal,
+        working_directory: working_directory.filter(|path| !path.is_empty()),
+        file_path: path.to_path_buf(),
+    })
+}
+
+fn list_contains(list: &str, value: &str) -> bool {
+    list.split(';').any(|item| item == value)
+}
+
+fn unescape_value(value: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('s') => unescaped.push(' '),
+            Some('n') => unescaped.push('\n'),
+            Some('t') => unescaped.push('\t'),
+            Some('r') => unescaped.push('\r'),
+            Some('\\') => unescaped.push('\\'),
+            Some(other) => {
+                // keep other escapes for the Exec key's own quoting rules
+                unescaped.push('\\');
+                unescaped.push(other);
+            }
+            None => unescaped.push('\\'),
+        }
+    }
+
+    unescaped
+}
+
+// Splits an Exec value into arguments. Arguments may be quoted with double quotes, inside
+// of which `"`, `` ` ``, `$` and `\` are escaped with a backslash.
+fn split_exec(exec: &str) -> Option<Vec<String>> {
+    let mut arguments: Vec<String> = Vec::new();
+    let mut argument = String::new();
+    let mut in_argument = false;
+
+    let mut chars = exec.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            ' ' | '\t' | '\n' => {
+                if in_argument {
+                    arguments.push(std::mem::take(&mut argument));
+                    in_argument = false;
+                }
+            }
+            '"' => {
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => argument.push(chars.next()?),
+                        Some(c) => argument.push(c),
+                        None => return None,
+                    }
+                }
+                in_argument = true;
+            }
+            '\\' => {
+                argument.push(chars.next()?);
+                in_argument = true;
+            }
+            c => {
+                argument.push(c);
+                in_argument = true;
+            }
+        }
+    }
+
+    if in_argument {
+        arguments.push(argument);
+    }
+
+    Some(arguments)
+}
+
+fn is_executable_available(program: &str) -> bool {
+    let executable_at = |path: &Path| fs::metadata(path).is_ok_and(|m| is_executable(&m));
+
+    if program.contains('/') {
+        return executable_at(Path::new(program));
+    }
+
+    match env::var_os("PATH") {
+        Some(path) => {
+            env::split_paths(&path).any(|directory| executable_at(&directory.join(program)))
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(exec: &str) -> DesktopEntry {
+        DesktopEntry {
+            name: String::from("Firefox"),
+            generic_name: None,
+            comment: None,
+            keywords: Vec::new(),
+            exec: exec.to_string(),
+            icon: Some(String::from("firefox")),
+            terminal: false,
+            working_directory: None,
+            file_path: PathBuf::from("/usr/share/applications/firefox.desktop"),
+        }
+    }
+
+    fn strings(words: &[&str]) -> Option<Vec<String>> {
+        Some(words.iter().map(|word| word.to_string()).collect())
+    }
+
+    #[test]
+    fn splits_exec_with_quotes() {
+        assert_eq!(
+            split_exec(r#"sh -c "echo \"a b\" \$HOME \\ \`" x\ y"#),
+            strings(&["sh", "-c", r#"echo "a b" $HOME \ `"#, "x y"])
+        );
+        assert_eq!(split_exec("a \"b"), None);
+        assert_eq!(split_exec("a \\"), None);
+    }
+
+    #[test]
+    fn unescapes_values() {
+        assert_eq!(unescape_value(r"a\sb\tc\nd\\e"), "a b\tc\nd\\e");
+        // other escapes are left for the Exec key's quoting
+        assert_eq!(unescape_value(r#"a\"b\;"#), r#"a\"b\;"#);
+        assert_eq!(unescape_value(r"a\"), r"a\");
+    }
+
+    #[test]
+    fn drops_file_and_url_field_codes() {
+        assert_eq!(
+            entry("firefox %u --new-window %F").command("xterm -e"),
+            strings(&["firefox", "--new-window"])
+        );
+    }
+
+    #[test]
+    fn expands_field_codes() {
+        assert_eq!(
+            entry("app --name=%c --file=%k 100%% %i").command("xterm -e"),
+            strings(&[
+                "app",
+                "--name=Firefox",
+                "--file=/usr/share/applications/firefox.desktop",
+                "100%",
+                "--icon",
+                "firefox",
+            ])
+        );
+        assert_eq!(
+            entry("app --%z").command("xterm -e"),
+            strings(&["app", "--"])
+        );
+    }
+
+    #[test]
+    fn runs_terminal_applications_in_the_terminal() {
+        let entry = DesktopEntry {
+            terminal: true,
+            ..entry("htop")
+        };
+
+        assert_eq!(
+            entry.command("alacritty -e"),
+            strings(&["alacritty", "-e", "htop"])
+        );
+    }
+
+    #[test]
+    fn has_no_command_without_words() {
+        assert_eq!(entry("%f").command("xterm -e"), None);
+    }
+}
@@ -0,0 +1,205 @@
+use std::{
+    io::{self, BufRead},
+    sync::mpsc::Sender,
+};
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::item::{Item, Selection, Source};
+
+// How the items on stdin are read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum InputFormat {
+    /// An item per line
+    #[default]
+    Lines,
+    /// A JSON object per line with `label`, `value`, `description`, `icon`, `keywords` and `meta`
+    Jsonl,
+}
+
+// How the selection is printed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OutputFormat {
+    /// The selected item's value or the typed text
+    #[default]
+    Text,
+    /// A JSON object with the selected items, the typed text and how it was accepted
+    Json,
+}
+
+// An item read with `--input-format jsonl`. Either the label or the value has to be set, the
+// other one defaults to it.
+#[derive(Deserialize)]
+struct JsonItem {
+    label: Option<String>,
+    value: Option<String>,
+    description: Option<String>,
+    icon: Option<String>,
+    #[serde(default)]
+    keywords: Vec<String>,
+    #[serde(default)]
+    meta: Map<String, Value>,
+}
+
+#[derive(Serialize)]
+struct JsonSelection<'a> {
+    selection: Vec<&'a Item>,
+    query: &'a str,
+    action: &'a str,
+    key: Option<&'a str>,
+}
+
+// Sends every line on its own so items show up as soon as a slow producer outputs them. Lines
+// that aren't valid items are skipped with a warning.
+pub fn read_items(
+    reader: impl BufRead,
+    format: InputFormat,
+    sender: &Sender<Vec<Item>>,
+) -> io::Result<()> {
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+
+        // empty lines are skipped, lines of only whitespace are items unless they should be JSON
+        let item = match format {
+            InputFormat::Lines if line.is_empty() => continue,
+            InputFormat::Lines => Item::new(line, Source::Stdin),
+            InputFormat::Jsonl if line.trim().is_empty() => continue,
+            InputFormat::Jsonl => match parse_item(&line) {
+                Ok(item) => item,
+                Err(e) => {
+                    eprintln!("Warning: line {}: {}", index + 1, e);
+                    continue;
+                }
+            },
+        };
+
+        // the menu was closed
+        if sender.send(vec![item]).is_err() {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_item(line: &str) -> Result<Item, String> {
+    let item: JsonItem = serde_json::from_str(line).map_err(|e| e.to_string())?;
+
+    let (label, value) = match (item.label, item.value) {
+        (Some(label), Some(value)) => (label, value),
+        (Some(label), None) => (label.clone(), label),
+        (None, Some(value)) => (value.clone(), value),
+        (None, None) => return Err(String::from("expected a `label` or a `value`")),
+    };
+
+    Ok(Item {
+        label,
+        value,
+        description: item.description,
+        icon: item.icon,
+        keywords: item.keywords,
+        source: Source::Stdin,
+        metadata: item.meta,
+    })
+}
+
+pub fn print_selection(selection: &Selection, format: OutputFormat) -> io::Result<()> {
+    match format {
+        OutputFormat::Text => match selection.item {
+            Some(ref item) => println!("{}", item.value),
+            None => println!("{}", selection.query),
+        },
+        OutputFormat::Json => {
+            let output = JsonSelection {
+                selection: selection.item.iter().collect(),
+                query: &selection.query,
+                action: selection.action,
+                key: selection.key.as_deref(),
+            };
+
+            println!("{}", serde_json::to_string(&output)?);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use serde_json::json;
+
+    use super::*;
+
+    fn read(input: &str, format: InputFormat) -> Vec<Item> {
+        let (sender, receiver) = mpsc::channel();
+        read_items(input.as_bytes(), format, &sender).unwrap();
+        drop(sender);
+
+        receiver.into_iter().flatten().collect()
+    }
+
+    fn labels(items: &[Item]) -> Vec<&str> {
+        items.iter().map(|item| item.label.as_str()).collect()
+    }
+
+    #[test]
+    fn reads_a_line_per_item() {
+        let items = read("a\n\n  \nb c\n", InputFormat::Lines);
+
+        assert_eq!(labels(&items), ["a", "  ", "b c"]);
+        assert_eq!(items[2].value, "b c");
+        assert_eq!(items[2].source, Source::Stdin);
+    }
+
+    #[test]
+    fn reads_json_lines_skipping_invalid_ones() {
+        let input = concat!(
+            r#"{"label": "a"}"#,
+            "\n  \n",
+            "not json\n",
+            r#"{"description": "no label or value"}"#,
+            "\n",
+            r#"{"value": "b"}"#,
+            "\n",
+        );
+
+        assert_eq!(labels(&read(input, InputFormat::Jsonl)), ["a", "b"]);
+    }
+
+    #[test]
+    fn defaults_the_label_and_value_to_each_other() {
+        let item = parse_item(r#"{"value": "firefox --private-window"}"#).unwrap();
+        assert_eq!(item.label, "firefox --private-window");
+        assert_eq!(item.value, "firefox --private-window");
+
+        let item = parse_item(r#"{"label": "Firefox"}"#).unwrap();
+        assert_eq!(item.label, "Firefox");
+        assert_eq!(item.value, "Firefox");
+
+        let item = parse_item(r#"{"label": "Firefox", "value": "firefox"}"#).unwrap();
+        assert_eq!(item.label, "Firefox");
+        assert_eq!(item.value, "firefox");
+
+        assert!(parse_item(r#"{"icon": "firefox"}"#).is_err());
+        assert!(parse_item(r#"{"label": 1}"#).is_err());
+    }
+
+    #[test]
+    fn passes_the_other_fields_through() {
+        let item = parse_item(
+            r#"{"label": "a", "description": "b", "icon": "c", "keywords": ["d"], "meta": {"id": 1}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(item.description.as_deref(), Some("b"));
+        assert_eq!(item.icon.as_deref(), Some("c"));
+        assert_eq!(item.keywords, ["d"]);
+        assert_eq!(item.metadata.get("id"), Some(&json!(1)));
+    }
+}
@@ -0,0 +1,79 @@
+use std::{error::Error, fmt, io, path::PathBuf};
+
+#[derive(Debug)]
+pub enum RunnerError {
+    InvalidColor {
+        value: String,
+        argument: String,
+    },
+    FontNotFound {
+        family: String,
+    },
+    FontLoad {
+        path: String,
+        message: String,
+    },
+    Sdl {
+        context: &'static str,
+        message: String,
+    },
+    UnreadableDirectory {
+        path: PathBuf,
+        source: io::Error,
+    },
+    Config(String),
+    Io(io::Error),
+}
+
+impl RunnerError {
+    pub fn sdl(context: &'static str) -> impl Fn(String) -> Self {
+        move |message| Self::Sdl { context, message }
+    }
+
+    // 1 is left for a cancelled menu in dmenu mode
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::Config(_) => 2,
+            Self::InvalidColor { .. } => 3,
+            Self::FontNotFound { .. } | Self::FontLoad { .. } => 4,
+            Self::Sdl { .. } => 5,
+            Self::UnreadableDirectory { .. } | Self::Io(_) => 6,
+        }
+    }
+}
+
+impl fmt::Display for RunnerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidColor { value, argument } => {
+                write!(f, "invalid color `{}` for {}", value, argument)
+            }
+            Self::FontNotFound { family } => write!(f, "could not find a font for `{}`", family),
+            Self::FontLoad { path, message } => {
+                write!(f, "could not load font {}: {}", path, message)
+            }
+            Self::Sdl { context, message } => write!(f, "{}: {}", context, message),
+            Self::UnreadableDirectory { path, source } => {
+                write!(f, "could not read directory {}: {}", path.display(), source)
+            }
+            Self::Config(message) => write!(f, "{}", message),
+            Self::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for RunnerError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::UnreadableDirectory { source, .. } => Some(source),
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for RunnerError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
@@ -1,43 +1,223 @@
-use std::{error::Error, fs, path::Path};
+use std::{
+    collections::{HashMap, HashSet},
+    env, fs, io,
+    os::unix::fs::MetadataExt,
+    path::{Path, PathBuf},
+};
 
-pub fn get_executables() -> Result<Vec<String>, Box<dyn Error>> {
-    let mut executables: Vec<String> = Vec::new();
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::RunnerError,
+    item::{Item, Source},
+    utils::{is_executable, xdg_dir},
+};
+
+const DEFAULT_PATH: &str = "/usr/local/bin:/usr/bin:/bin";
+
+// The executables directly in a directory and its subdirectories, as of the directory's state.
+// Adding or removing a file changes the mtime, changing a file's permissions doesn't, which is
+// what --rebuild-cache is for.
+#[derive(Serialize, Deserialize)]
+struct CachedDirectory {
+    state: DirectoryState,
+    executables: Vec<String>,
+    subdirectories: Vec<PathBuf>,
+}
+
+// What has to stay the same for a cached directory to be reused. Symlinked directories are
+// compared by their target, Nix profiles switch between store directories that all have the
+// same mtime. The inode and ctime catch changes that coarse mtimes, e.g. on network file
+// systems, can miss.
+#[derive(Serialize, Deserialize, PartialEq)]
+struct DirectoryState {
+    target: PathBuf,
+    inode: u64,
+    mtime: (i64, i64),
+    ctime: (i64, i64),
+}
+
+type Index = HashMap<PathBuf, CachedDirectory>;
+
+// Directories that can't be read are skipped with a warning, a broken $PATH entry shouldn't
+// keep the menu from opening. Only directories that changed since the last launch are read.
+// The items are described by the executable's full path.
+pub fn get_executables(recursive: bool, rebuild_cache: bool) -> Vec<Item> {
+    let cache_path = get_cache_path();
+
+    let mut cache: Index = match cache_path {
+        Some(ref path) if !rebuild_cache => fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default(),
+        _ => Index::new(),
+    };
+
+    let mut index = Index::new();
+    let mut changed = false;
+
+    let mut executables: Vec<Item> = Vec::new();
+    let mut seen: HashSet<String> = HashSet::new();
+
+    for directory in get_search_paths() {
+        let mut directories = vec![directory];
+
+        while let Some(directory) = directories.pop() {
+            if index.contains_key(&directory) {
+                continue;
+            }
 
-    get_files(Path::new("/bin"), &mut executables)?;
-    match home::cargo_home() {
-        Ok(cargo_home) => {
-            get_files(&cargo_home.join("bin"), &mut executables)?;
+            let entry = match scan_directory(&directory, &mut cache) {
+                Ok(Some((entry, rescanned))) => {
+                    changed |= rescanned;
+                    entry
+                }
+                Ok(None) => continue,
+                Err(e) => {
+                    eprintln!("Warning: {}", e);
+                    continue;
+                }
+            };
+
+            // earlier directories take precedence, like in a shell
+            for executable in &entry.executables {
+                if seen.insert(executable.clone()) {
+                    executables.push(Item {
+                        description: Some(directory.join(executable).to_string_lossy().into()),
+                        ..Item::new(executable.clone(), Source::Executable)
+                    });
+                }
+            }
+
+            if recursive {
+                directories.extend(entry.subdirectories.iter().rev().cloned());
+            }
+
+            index.insert(directory, entry);
         }
-        Err(_) => (),
     }
 
-    Ok(executables)
-}
+    // directories that aren't searched anymore are dropped from the cache as well
+    changed |= !cache.is_empty();
 
-pub fn get_files(path: &Path, files: &mut Vec<String>) -> Result<(), Box<dyn Error>> {
-    if !path.exists() {
-        return Ok(());
+    if changed {
+        if let Some(ref path) = cache_path {
+            if let Err(e) = write_cache(path, &index) {
+                eprintln!("Warning: could not write {}: {}", path.display(), e);
+            }
+        }
     }
 
-    let dirs = fs::read_dir(path)?;
+    // in $PATH order, the menu sorts all items once they arrive
+    executables
+}
+
+// The directories in $PATH in order, followed by Cargo's bin directory if it isn't in there
+fn get_search_paths() -> Vec<PathBuf> {
+    let path = env::var_os("PATH").unwrap_or_else(|| DEFAULT_PATH.into());
 
-    for e in dirs {
-        let entry = e.unwrap();
-        let file_type = entry.file_type()?;
+    let mut directories: Vec<PathBuf> = Vec::new();
 
-        if file_type.is_dir() {
-            get_files(&entry.path(), files)?;
+    for directory in env::split_paths(&path) {
+        // empty entries mean the current directory, which isn't useful for a launcher
+        if directory.as_os_str().is_empty() || directories.contains(&directory) {
             continue;
         }
-        match entry.file_name().into_string() {
-            Ok(file_name) => {
-                if !files.contains(&file_name) {
-                    files.push(file_name)
-                }
+        directories.push(directory);
+    }
+
+    if let Ok(cargo_home) = home::cargo_home() {
+        let cargo_bin = cargo_home.join("bin");
+        if !directories.contains(&cargo_bin) {
+            directories.push(cargo_bin);
+        }
+    }
+
+    directories
+}
+
+// Takes the directory's entry from the cache if it didn't change, otherwise it's read again.
+// Also returns whether it was read. Paths that aren't directories are skipped.
+fn scan_directory(
+    path: &Path,
+    cache: &mut Index,
+) -> Result<Option<(CachedDirectory, bool)>, RunnerError> {
+    let unreadable = |source| RunnerError::UnreadableDirectory {
+        path: path.to_path_buf(),
+        source,
+    };
+
+    let metadata = match fs::metadata(path) {
+        Ok(metadata) if metadata.is_dir() => metadata,
+        _ => return Ok(None),
+    };
+
+    let state = DirectoryState {
+        target: fs::canonicalize(path).map_err(unreadable)?,
+        inode: metadata.ino(),
+        mtime: (metadata.mtime(), metadata.mtime_nsec()),
+        ctime: (metadata.ctime(), metadata.ctime_nsec()),
+    };
+
+    if let Some(cached) = cache.remove(path) {
+        if cached.state == state {
+            return Ok(Some((cached, false)));
+        }
+    }
+
+    let mut executables: Vec<String> = Vec::new();
+    let mut subdirectories: Vec<PathBuf> = Vec::new();
+
+    for entry in fs::read_dir(path).map_err(unreadable)?.flatten() {
+        // follow symlinks so links to executables count and dangling links are skipped
+        let metadata = match fs::metadata(entry.path()) {
+            Ok(metadata) => metadata,
+            Err(_) => continue,
+        };
+
+        if metadata.is_dir() {
+            // symlinked directories could loop
+            if !entry.file_type().is_ok_and(|t| t.is_symlink()) {
+                subdirectories.push(entry.path());
             }
-            Err(_) => {}
+            continue;
+        }
+
+        if !is_executable(&metadata) {
+            continue;
+        }
+
+        if let Ok(file_name) = entry.file_name().into_string() {
+            executables.push(file_name);
         }
     }
 
-    Ok(())
+    // read_dir's order is arbitrary, this keeps the results stable
+    executables.sort();
+    subdirectories.sort();
+
+    Ok(Some((
+        CachedDirectory {
+            state,
+            executables,
+            subdirectories,
+        },
+        true,
+    )))
+}
+
+fn get_cache_path() -> Option<PathBuf> {
+    Some(
+        xdg_dir("XDG_CACHE_HOME", ".cache")?
+            .join("practicalrunner")
+            .join("executables.json"),
+    )
+}
+
+fn write_cache(path: &Path, index: &Index) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    fs::write(path, serde_json::to_string(index)?)
 }
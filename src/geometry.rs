@@ -0,0 +1,168 @@
+use std::{fmt, str::FromStr};
+
+use clap::ValueEnum;
+use sdl2::rect::Rect;
+use serde::Deserialize;
+
+// A size in pixels or relative to the display, e.g. `600` or `50%`
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(try_from = "LengthValue")]
+pub enum Length {
+    Pixels(u32),
+    Percent(f64),
+}
+
+impl Length {
+    pub fn resolve(&self, total: u32) -> u32 {
+        match self {
+            Self::Pixels(pixels) => *pixels,
+            Self::Percent(percent) => (total as f64 * percent / 100.0).round() as u32,
+        }
+    }
+}
+
+impl FromStr for Length {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "invalid length `{}`, expected pixels or a percentage",
+                value
+            )
+        };
+
+        match value.trim().strip_suffix('%') {
+            Some(percent) => match percent.trim().parse::<f64>() {
+                Ok(percent) if (0.0..=100.0).contains(&percent) => Ok(Self::Percent(percent)),
+                _ => Err(invalid()),
+            },
+            None => value
+                .trim()
+                .parse()
+                .map(Self::Pixels)
+                .map_err(|_| invalid()),
+        }
+    }
+}
+
+impl fmt::Display for Length {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pixels(pixels) => write!(f, "{}", pixels),
+            Self::Percent(percent) => write!(f, "{}%", percent),
+        }
+    }
+}
+
+// Lengths in the config file can be numbers or strings
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum LengthValue {
+    Pixels(u32),
+    Text(String),
+}
+
+impl TryFrom<LengthValue> for Length {
+    type Error = String;
+
+    fn try_from(value: LengthValue) -> Result<Self, Self::Error> {
+        match value {
+            LengthValue::Pixels(pixels) => Ok(Self::Pixels(pixels)),
+            LengthValue::Text(text) => text.parse(),
+        }
+    }
+}
+
+// Where the window is placed on the display
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Position {
+    #[default]
+    Center,
+    Top,
+    Bottom,
+    Left,
+    Right,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+    /// Centered on the mouse cursor
+    Mouse,
+    /// A full-width bar at the top of the display, like dmenu
+    TopBar,
+    /// A full-width bar at the bottom of the display
+    BottomBar,
+}
+
+impl Position {
+    pub fn is_bar(&self) -> bool {
+        matches!(self, Self::TopBar | Self::BottomBar)
+    }
+
+    // The top-left corner of a window of the given size within the display bounds. Windows
+    // placed at the mouse are kept inside the display.
+    pub fn place(
+        &self,
+        bounds: Rect,
+        size: (u32, u32),
+        offset: (i32, i32),
+        mouse: (i32, i32),
+    ) -> (i32, i32) {
+        let (width, height) = (size.0 as i32, size.1 as i32);
+
+        let left = bounds.x();
+        let right = bounds.x() + bounds.width() as i32 - width;
+        let center_x = bounds.x() + (bounds.width() as i32 - width) / 2;
+        let top = bounds.y();
+        let bottom = bounds.y() + bounds.height() as i32 - height;
+        let center_y = bounds.y() + (bounds.height() as i32 - height) / 2;
+
+        let (x, y) = match self {
+            Self::Center => (center_x, center_y),
+            Self::Top => (center_x, top),
+            Self::Bottom => (center_x, bottom),
+            Self::Left => (left, center_y),
+            Self::Right => (right, center_y),
+            Self::TopLeft => (left, top),
+            Self::TopRight => (right, top),
+            Self::BottomLeft => (left, bottom),
+            Self::BottomRight => (right, bottom),
+            Self::Mouse => (
+                (mouse.0 - width / 2).clamp(left, right.max(left)),
+                (mouse.1 - height / 2).clamp(top, bottom.max(top)),
+            ),
+            Self::TopBar => (left, top),
+            Self::BottomBar => (left, bottom),
+        };
+
+        (x + offset.0, y + offset.1)
+    }
+}
+
+// How the items are arranged in the window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Layout {
+    /// A list of rows under the input
+    #[default]
+    Vertical,
+    /// The prompt, input and items on a single line, like dmenu
+    Horizontal,
+    /// A grid of icons with the names under them, like an app drawer
+    Grid,
+}
+
+// Where the items' descriptions go
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DescriptionPosition {
+    /// Right-aligned on the same line as the label
+    #[default]
+    Right,
+    /// On a second line under the label
+    Below,
+    /// Not shown at all
+    Hidden,
+}
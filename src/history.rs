@@ -0,0 +1,124 @@
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::utils::xdg_dir;
+
+// After this many seconds a launch only counts half as much
+const HALF_LIFE: f64 = 60.0 * 60.0 * 24.0 * 7.0;
+
+struct HistoryEntry {
+    score: f64,
+    last_used: u64,
+}
+
+impl HistoryEntry {
+    fn frecency(&self, now: u64) -> f64 {
+        let elapsed = now.saturating_sub(self.last_used) as f64;
+
+        self.score * 0.5_f64.powf(elapsed / HALF_LIFE)
+    }
+}
+
+pub struct History {
+    path: PathBuf,
+    entries: HashMap<String, HistoryEntry>,
+    max_size: usize,
+}
+
+impl History {
+    pub fn load(max_size: usize) -> Option<Self> {
+        let path = get_history_path()?;
+
+        let mut entries: HashMap<String, HistoryEntry> = HashMap::new();
+
+        // a missing or unreadable history just starts out empty
+        if let Ok(content) = fs::read_to_string(&path) {
+            for line in content.lines() {
+                let mut fields = line.splitn(3, '\t');
+
+                let (Some(score), Some(last_used), Some(item)) =
+                    (fields.next(), fields.next(), fields.next())
+                else {
+                    continue;
+                };
+
+                let (Ok(score), Ok(last_used)) = (score.parse(), last_used.parse()) else {
+                    continue;
+                };
+
+                entries.insert(item.to_string(), HistoryEntry { score, last_used });
+            }
+        }
+
+        Some(Self {
+            path,
+            entries,
+            max_size,
+        })
+    }
+
+    pub fn frecencies(&self) -> HashMap<String, f64> {
+        let now = now();
+
+        self.entries
+            .iter()
+            .map(|(item, entry)| (item.clone(), entry.frecency(now)))
+            .collect()
+    }
+
+    pub fn record(&mut self, item: &str) {
+        let now = now();
+
+        let score = self
+            .entries
+            .get(item)
+            .map(|entry| entry.frecency(now))
+            .unwrap_or(0.0);
+
+        self.entries.insert(
+            item.to_string(),
+            HistoryEntry {
+                score: score + 1.0,
+                last_used: now,
+            },
+        );
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let now = now();
+
+        let mut entries: Vec<(&String, &HistoryEntry)> = self.entries.iter().collect();
+        entries.sort_by(|a, b| b.1.frecency(now).total_cmp(&a.1.frecency(now)));
+        entries.truncate(self.max_size);
+
+        let mut content = String::new();
+        for (item, entry) in entries {
+            content += &format!("{}\t{}\t{}\n", entry.score, entry.last_used, item);
+        }
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(&self.path, content)
+    }
+}
+
+fn get_history_path() -> Option<PathBuf> {
+    Some(
+        xdg_dir("XDG_STATE_HOME", ".local/state")?
+            .join("practicalrunner")
+            .join("history"),
+    )
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
@@ -0,0 +1,353 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use resvg::{
+    tiny_skia::{FilterQuality, Pixmap, PixmapPaint, Transform},
+    usvg,
+};
+
+use crate::{config_file::get_config_dir, desktop_entries::get_data_dirs};
+
+const EXTENSIONS: [&str; 2] = ["png", "svg"];
+const FALLBACK_THEME: &str = "hicolor";
+
+enum DirectoryType {
+    Fixed,
+    Scalable { min_size: u32, max_size: u32 },
+    Threshold { threshold: u32 },
+}
+
+struct ThemeDirectory {
+    path: String,
+    size: u32,
+    kind: DirectoryType,
+}
+
+impl ThemeDirectory {
+    fn matches_size(&self, size: u32) -> bool {
+        match self.kind {
+            DirectoryType::Fixed => self.size == size,
+            DirectoryType::Scalable { min_size, max_size } => (min_size..=max_size).contains(&size),
+            DirectoryType::Threshold { threshold } => {
+                self.size.saturating_sub(threshold) <= size && size <= self.size + threshold
+            }
+        }
+    }
+
+    fn size_distance(&self, size: u32) -> u32 {
+        match self.kind {
+            DirectoryType::Fixed => self.size.abs_diff(size),
+            DirectoryType::Scalable { min_size, max_size } => min_size
+                .saturating_sub(size)
+                .max(size.saturating_sub(max_size)),
+            DirectoryType::Threshold { threshold } => self
+                .size
+                .saturating_sub(threshold)
+                .saturating_sub(size)
+                .max(size.saturating_sub(self.size + threshold)),
+        }
+    }
+}
+
+struct IconTheme {
+    name: String,
+    directories: Vec<ThemeDirectory>,
+}
+
+// Finds icons by name following the freedesktop icon theme spec: the theme and the themes it
+// inherits from are searched in order, then hicolor and finally the unthemed icons
+pub struct IconLookup {
+    base_dirs: Vec<PathBuf>,
+    themes: Vec<IconTheme>,
+    files: FileIndex,
+    cache: HashMap<(String, u32), Option<PathBuf>>,
+}
+
+// The file names in icon directories, each directory is read once instead of checking every
+// possible file of every icon, most of which don't exist
+#[derive(Default)]
+struct FileIndex {
+    directories: HashMap<PathBuf, HashSet<String>>,
+}
+
+impl FileIndex {
+    fn find(&mut self, dir: &Path, icon: &str) -> Option<PathBuf> {
+        let files = self
+            .directories
+            .entry(dir.to_path_buf())
+            .or_insert_with(|| read_file_names(dir));
+
+        EXTENSIONS
+            .iter()
+            .map(|extension| format!("{}.{}", icon, extension))
+            .find(|file_name| files.contains(file_name))
+            .map(|file_name| dir.join(file_name))
+    }
+}
+
+impl IconLookup {
+    pub fn new(theme: Option<&str>) -> Self {
+        let mut base_dirs: Vec<PathBuf> = Vec::new();
+        if let Some(home) = home::home_dir() {
+            base_dirs.push(home.join(".icons"));
+        }
+        base_dirs.extend(get_data_dirs().into_iter().map(|dir| dir.join("icons")));
+        base_dirs.push(PathBuf::from("/usr/share/pixmaps"));
+
+        let mut lookup = Self {
+            base_dirs,
+            themes: Vec::new(),
+            files: FileIndex::default(),
+            cache: HashMap::new(),
+        };
+
+        let theme = theme
+            .map(str::to_string)
+            .or_else(get_gtk_icon_theme)
+            .unwrap_or_else(|| String::from(FALLBACK_THEME));
+
+        lookup.load_theme(&theme);
+        lookup.load_theme(FALLBACK_THEME);
+
+        lookup
+    }
+
+    // Loads a theme and the themes it inherits from, depth first
+    fn load_theme(&mut self, name: &str) {
+        if self.themes.iter().any(|theme| theme.name == name) {
+            return;
+        }
+
+        let Some(index) = self
+            .base_dirs
+            .iter()
+            .find_map(|dir| fs::read_to_string(dir.join(name).join("index.theme")).ok())
+        else {
+            return;
+        };
+
+        let (directories, inherits) = parse_index_theme(&index);
+
+        self.themes.push(IconTheme {
+            name: name.to_string(),
+            directories,
+        });
+
+        for parent in inherits {
+            self.load_theme(&parent);
+        }
+    }
+
+    pub fn find(&mut self, icon: &str, size: u32) -> Option<PathBuf> {
+        if let Some(path) = self.cache.get(&(icon.to_string(), size)) {
+            return path.clone();
+        }
+
+        let path = self.find_uncached(icon, size);
+        self.cache.insert((icon.to_string(), size), path.clone());
+
+        path
+    }
+
+    fn find_uncached(&mut self, icon: &str, size: u32) -> Option<PathBuf> {
+        if icon.is_empty() {
+            return None;
+        }
+
+        // desktop entries may point to a file directly
+        let path = Path::new(icon);
+        if path.is_absolute() {
+            return path.is_file().then(|| path.to_path_buf());
+        }
+
+        // or use a file name instead of an icon name, which the spec doesn't allow but happens
+        let icon = EXTENSIONS
+            .iter()
+            .find_map(|extension| icon.strip_suffix(&format!(".{}", extension)))
+            .unwrap_or(icon);
+
+        for theme in &self.themes {
+            if let Some(path) = find_in_theme(&self.base_dirs, &mut self.files, theme, icon, size) {
+                return Some(path);
+            }
+        }
+
+        self.base_dirs
+            .iter()
+            .find_map(|dir| self.files.find(dir, icon))
+    }
+}
+
+// An icon of exactly the size if there is one, otherwise the closest one
+fn find_in_theme(
+    base_dirs: &[PathBuf],
+    files: &mut FileIndex,
+    theme: &IconTheme,
+    icon: &str,
+    size: u32,
+) -> Option<PathBuf> {
+    let mut closest: Option<(u32, PathBuf)> = None;
+
+    for directory in &theme.directories {
+        let distance = directory.size_distance(size);
+        if closest
+            .as_ref()
+            .is_some_and(|(closest_distance, _)| *closest_distance <= distance)
+            && !directory.matches_size(size)
+        {
+            continue;
+        }
+
+        for base_dir in base_dirs {
+            let Some(path) = files.find(&base_dir.join(&theme.name).join(&directory.path), icon)
+            else {
+                continue;
+            };
+
+            if directory.matches_size(size) {
+                return Some(path);
+            }
+            closest = Some((distance, path));
+            break;
+        }
+    }
+
+    closest.map(|(_, path)| path)
+}
+
+// Missing directories are just empty
+fn read_file_names(dir: &Path) -> HashSet<String> {
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|entry| entry.file_name().into_string().ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// The directories of the theme and the themes it inherits from
+fn parse_index_theme(index: &str) -> (Vec<ThemeDirectory>, Vec<String>) {
+    let mut sections: HashMap<&str, HashMap<&str, &str>> = HashMap::new();
+    let mut section = "";
+
+    for line in index.lines() {
+        let line = line.trim();
+
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = name;
+        } else if let Some((key, value)) = line.split_once('=') {
+            sections
+                .entry(section)
+                .or_default()
+                .insert(key.trim(), value.trim());
+        }
+    }
+
+    let Some(theme) = sections.get("Icon Theme") else {
+        return (Vec::new(), Vec::new());
+    };
+
+    let list = |key: &str| -> Vec<String> {
+        theme
+            .get(key)
+            .map(|value| {
+                value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|value| !value.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    let directories = list("Directories")
+        .into_iter()
+        .filter_map(|path| {
+            let keys = sections.get(path.as_str())?;
+            let number = |key: &str| keys.get(key).and_then(|value| value.parse::<u32>().ok());
+
+            let size = number("Size")?;
+
+            // scaled directories are for HiDPI, which the window doesn't use
+            if number("Scale").is_some_and(|scale| scale != 1) {
+                return None;
+            }
+
+            let kind = match keys.get("Type").copied().unwrap_or("Threshold") {
+                "Fixed" => DirectoryType::Fixed,
+                "Scalable" => DirectoryType::Scalable {
+                    min_size: number("MinSize").unwrap_or(size),
+                    max_size: number("MaxSize").unwrap_or(size),
+                },
+                _ => DirectoryType::Threshold {
+                    threshold: number("Threshold").unwrap_or(2),
+                },
+            };
+
+            Some(ThemeDirectory { path, size, kind })
+        })
+        .collect();
+
+    (directories, list("Inherits"))
+}
+
+// The icon theme GTK applications use, there is no desktop independent setting for it
+fn get_gtk_icon_theme() -> Option<String> {
+    let settings = get_config_dir()?
+        .parent()?
+        .join("gtk-3.0")
+        .join("settings.ini");
+
+    fs::read_to_string(settings)
+        .ok()?
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .find(|(key, _)| key.trim() == "gtk-icon-theme-name")
+        .map(|(_, value)| value.trim().trim_matches('"').to_string())
+}
+
+// Renders a PNG or SVG icon scaled to fit into a square of the size
+pub fn render_icon(path: &Path, size: u32) -> Option<Pixmap> {
+    let data = fs::read(path).ok()?;
+    let mut pixmap = Pixmap::new(size, size)?;
+
+    if path.extension().is_some_and(|extension| extension == "svg") {
+        let tree = usvg::Tree::from_data(&data, &usvg::Options::default()).ok()?;
+
+        let transform = fit(tree.size().width(), tree.size().height(), size);
+        resvg::render(&tree, transform, &mut pixmap.as_mut());
+    } else {
+        let image = Pixmap::decode_png(&data).ok()?;
+
+        let transform = fit(image.width() as f32, image.height() as f32, size);
+        pixmap.draw_pixmap(
+            0,
+            0,
+            image.as_ref(),
+            &PixmapPaint {
+                quality: FilterQuality::Bicubic,
+                ..Default::default()
+            },
+            transform,
+            None,
+        );
+    }
+
+    Some(pixmap)
+}
+
+// Scales an image to fit into the square and centers it
+fn fit(width: f32, height: f32, size: u32) -> Transform {
+    let scale = size as f32 / width.max(height);
+
+    Transform::from_scale(scale, scale).post_translate(
+        (size as f32 - width * scale) / 2.0,
+        (size as f32 - height * scale) / 2.0,
+    )
+}
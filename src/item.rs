@@ -0,0 +1,60 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+// The metadata key with the path of the desktop file a desktop entry item was read from
+pub const DESKTOP_FILE_KEY: &str = "desktop-file";
+
+// Where an item came from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Source {
+    #[default]
+    Stdin,
+    Executable,
+    DesktopEntry,
+}
+
+// Something that can be selected in the menu. The label is what's shown and matched against
+// the input, the value is what's printed or run once the item is selected. Serialized for
+// `--output-format json`, like the objects read with `--input-format jsonl`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Item {
+    pub label: String,
+    pub value: String,
+    pub description: Option<String>,
+    // an icon name or path, items without one are looked up by their label unless they were
+    // read from stdin
+    pub icon: Option<String>,
+    // matched like the label, but not shown
+    pub keywords: Vec<String>,
+    #[serde(skip)]
+    pub source: Source,
+    // passed through as is, e.g. IDs attached by the program that provided the item
+    #[serde(rename = "meta")]
+    pub metadata: Map<String, Value>,
+}
+
+impl Item {
+    // An item that shows and outputs the same text
+    pub fn new(text: impl Into<String>, source: Source) -> Self {
+        let text = text.into();
+
+        Self {
+            label: text.clone(),
+            value: text,
+            source,
+            ..Default::default()
+        }
+    }
+}
+
+// What the menu was closed with
+#[derive(Debug, Clone)]
+pub struct Selection {
+    // `None` if the typed text was accepted as is
+    pub item: Option<Item>,
+    pub query: String,
+    // the name of the action, or `click` for a mouse click
+    pub action: &'static str,
+    // the key that was pressed, `None` for a mouse click
+    pub key: Option<String>,
+}
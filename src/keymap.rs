@@ -0,0 +1,411 @@
+use std::collections::HashMap;
+
+use sdl2::keyboard::{Keycode, Mod};
+
+use crate::config::KEYBINDINGS;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Accept,
+    AcceptInput,
+    Cancel,
+    Next,
+    Prev,
+    PageDown,
+    PageUp,
+    First,
+    Last,
+    Complete,
+    CursorLeft,
+    CursorRight,
+    WordLeft,
+    WordRight,
+    LineStart,
+    LineEnd,
+    DeleteCharBackward,
+    DeleteChar,
+    DeleteWord,
+    DeleteWordAlphanumeric,
+    KillToStart,
+    KillToEnd,
+    Yank,
+}
+
+pub const ACTIONS: &[(&str, Action, &str)] = &[
+    ("accept", Action::Accept, "Run or print the selected item"),
+    (
+        "accept-input",
+        Action::AcceptInput,
+        "Run or print the typed text as is",
+    ),
+    ("cancel", Action::Cancel, "Close the menu"),
+    ("next", Action::Next, "Select the next item"),
+    ("prev", Action::Prev, "Select the previous item"),
+    (
+        "page-down",
+        Action::PageDown,
+        "Select the item one page down",
+    ),
+    ("page-up", Action::PageUp, "Select the item one page up"),
+    ("first", Action::First, "Select the first item"),
+    ("last", Action::Last, "Select the last item"),
+    (
+        "complete",
+        Action::Complete,
+        "Replace the input with the selected item",
+    ),
+    (
+        "cursor-left",
+        Action::CursorLeft,
+        "Move the cursor one character left",
+    ),
+    (
+        "cursor-right",
+        Action::CursorRight,
+        "Move the cursor one character right",
+    ),
+    (
+        "word-left",
+        Action::WordLeft,
+        "Move the cursor one word left",
+    ),
+    (
+        "word-right",
+        Action::WordRight,
+        "Move the cursor one word right",
+    ),
+    (
+        "line-start",
+        Action::LineStart,
+        "Move the cursor to the start of the input",
+    ),
+    (
+        "line-end",
+        Action::LineEnd,
+        "Move the cursor to the end of the input",
+    ),
+    (
+        "delete-char-backward",
+        Action::DeleteCharBackward,
+        "Delete the character before the cursor",
+    ),
+    (
+        "delete-char",
+        Action::DeleteChar,
+        "Delete the character after the cursor",
+    ),
+    (
+        "delete-word",
+        Action::DeleteWord,
+        "Delete up to the previous whitespace",
+    ),
+    (
+        "delete-word-alphanumeric",
+        Action::DeleteWordAlphanumeric,
+        "Delete the previous alphanumeric word",
+    ),
+    (
+        "kill-to-start",
+        Action::KillToStart,
+        "Delete up to the start of the input",
+    ),
+    (
+        "kill-to-end",
+        Action::KillToEnd,
+        "Delete up to the end of the input",
+    ),
+    ("yank", Action::Yank, "Insert the last deleted text"),
+];
+
+// Only these modifiers are compared, which side of the keyboard they are on and lock keys like
+// num lock don't matter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+struct Modifiers {
+    ctrl: bool,
+    alt: bool,
+    shift: bool,
+    gui: bool,
+}
+
+impl From<Mod> for Modifiers {
+    fn from(keymod: Mod) -> Self {
+        Self {
+            ctrl: keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD),
+            alt: keymod.intersects(Mod::LALTMOD | Mod::RALTMOD),
+            shift: keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD),
+            gui: keymod.intersects(Mod::LGUIMOD | Mod::RGUIMOD),
+        }
+    }
+}
+
+pub struct Keymap {
+    bindings: HashMap<(Keycode, Modifiers), Action>,
+}
+
+impl Keymap {
+    // Builds the keymap from the default bindings followed by `key=action` bindings, where a
+    // later binding for the same key replaces an earlier one and the action `none` unbinds it
+    pub fn new(bindings: &[String]) -> Result<Self, String> {
+        let mut keymap = Self {
+            bindings: HashMap::new(),
+        };
+
+        for (key, action) in KEYBINDINGS {
+            keymap.bind(key, action)?;
+        }
+
+        for binding in bindings {
+            let Some((key, action)) = binding.rsplit_once('=') else {
+                return Err(format!(
+                    "invalid key binding `{}`, expected `key=action`",
+                    binding
+                ));
+            };
+
+            keymap.bind(key.trim(), action.trim())?;
+        }
+
+        Ok(keymap)
+    }
+
+    pub fn action(&self, keycode: Keycode, keymod: Mod) -> Option<Action> {
+        let modifiers = Modifiers::from(keymod);
+
+        // shifted keys fall back to their unshifted binding, e.g. shift+backspace
+        self.bindings
+            .get(&(keycode, modifiers))
+            .copied()
+            .or_else(|| {
+                self.bindings
+                    .get(&(
+                        keycode,
+                        Modifiers {
+                            shift: false,
+                            ..modifiers
+                        },
+                    ))
+                    .copied()
+            })
+    }
+
+    fn bind(&mut self, key: &str, action: &str) -> Result<(), String> {
+        let key_combination = parse_key(key)?;
+
+        if action == "none" {
+            self.bindings.remove(&key_combination);
+            return Ok(());
+        }
+
+        let Some((_, action, _)) = ACTIONS.iter().find(|(name, _, _)| *name == action) else {
+            return Err(format!(
+                "unknown action `{}`, run with --list-actions to see all actions",
+                action
+            ));
+        };
+
+        self.bindings.insert(key_combination, *action);
+
+        Ok(())
+    }
+
+    // The keys bound to an action, for --list-actions
+    pub fn keys(&self, action: Action) -> Vec<String> {
+        let mut keys: Vec<String> = self
+            .bindings
+            .iter()
+            .filter(|(_, a)| **a == action)
+            .map(|(&(keycode, modifiers), _)| format_key(keycode, modifiers))
+            .collect();
+
+        keys.sort();
+        keys
+    }
+}
+
+impl Action {
+    pub fn name(self) -> &'static str {
+        ACTIONS
+            .iter()
+            .find(|(_, action, _)| *action == self)
+            .map(|(name, _, _)| *name)
+            .unwrap_or_default()
+    }
+}
+
+// The name of a pressed key in the format used for bindings, e.g. `ctrl+Return`
+pub fn key_name(keycode: Keycode, keymod: Mod) -> String {
+    format_key(keycode, keymod.into())
+}
+
+fn format_key(keycode: Keycode, modifiers: Modifiers) -> String {
+    let mut key = String::new();
+
+    for (modifier, name) in [
+        (modifiers.ctrl, "ctrl+"),
+        (modifiers.alt, "alt+"),
+        (modifiers.shift, "shift+"),
+        (modifiers.gui, "super+"),
+    ] {
+        if modifier {
+            key += name;
+        }
+    }
+
+    key + &keycode.name().replace(' ', "-")
+}
+
+// Parses keys like `ctrl+shift+return` or `alt+page-down`. Key names are SDL key names,
+// compared case-insensitively and with dashes instead of spaces.
+fn parse_key(key: &str) -> Result<(Keycode, Modifiers), String> {
+    let mut modifiers = Modifiers::default();
+
+    let mut parts: Vec<&str> = key.split('+').collect();
+    let mut name = parts.pop().unwrap_or_default();
+
+    // allow binding the plus key itself, e.g. `ctrl++`
+    if name.is_empty() && parts.last() == Some(&"") {
+        parts.pop();
+        name = "+";
+    }
+
+    for modifier in parts {
+        match modifier.to_lowercase().as_str() {
+            "ctrl" | "control" => modifiers.ctrl = true,
+            "alt" => modifiers.alt = true,
+            "shift" => modifiers.shift = true,
+            "super" | "gui" | "meta" => modifiers.gui = true,
+            _ => return Err(format!("unknown modifier `{}` in `{}`", modifier, key)),
+        }
+    }
+
+    let name = match name.to_lowercase().as_str() {
+        "enter" => String::from("Return"),
+        "esc" => String::from("Escape"),
+        "del" => String::from("Delete"),
+        "pagedown" | "page-down" => String::from("PageDown"),
+        "pageup" | "page-up" => String::from("PageUp"),
+        // SDL only maps lowercase letters to their keycodes
+        lowercase if lowercase.chars().count() == 1 => lowercase.to_string(),
+        _ => name.replace('-', " "),
+    };
+
+    match Keycode::from_name(&name) {
+        Some(keycode) => Ok((keycode, modifiers)),
+        None => Err(format!("unknown key `{}` in `{}`", name, key)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CTRL: Modifiers = Modifiers {
+        ctrl: true,
+        alt: false,
+        shift: false,
+        gui: false,
+    };
+
+    #[test]
+    fn parses_keys_with_modifiers() {
+        assert_eq!(
+            parse_key("Ctrl+Shift+return"),
+            Ok((
+                Keycode::Return,
+                Modifiers {
+                    shift: true,
+                    ..CTRL
+                }
+            ))
+        );
+        assert_eq!(
+            parse_key("super+alt+a"),
+            Ok((
+                Keycode::A,
+                Modifiers {
+                    alt: true,
+                    gui: true,
+                    ..Modifiers::default()
+                }
+            ))
+        );
+        // letters are case-insensitive like every other key
+        assert_eq!(parse_key("ctrl+A"), Ok((Keycode::A, CTRL)));
+    }
+
+    #[test]
+    fn parses_the_plus_key() {
+        assert_eq!(parse_key("ctrl++"), Ok((Keycode::Plus, CTRL)));
+        assert_eq!(parse_key("+"), Ok((Keycode::Plus, Modifiers::default())));
+    }
+
+    #[test]
+    fn parses_key_names_with_dashes_and_aliases() {
+        assert_eq!(
+            parse_key("page-down"),
+            Ok((Keycode::PageDown, Modifiers::default()))
+        );
+        assert_eq!(parse_key("ctrl+PageUp"), Ok((Keycode::PageUp, CTRL)));
+        assert_eq!(
+            parse_key("keypad-enter"),
+            Ok((Keycode::KpEnter, Modifiers::default()))
+        );
+        assert_eq!(
+            parse_key("esc"),
+            Ok((Keycode::Escape, Modifiers::default()))
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_keys_and_modifiers() {
+        assert!(parse_key("hyper+a").is_err());
+        assert!(parse_key("ctrl+nope").is_err());
+        assert!(parse_key("").is_err());
+    }
+
+    #[test]
+    fn shifted_keys_fall_back_to_their_unshifted_binding() {
+        let keymap = Keymap::new(&[]).unwrap();
+
+        assert_eq!(
+            keymap.action(Keycode::Backspace, Mod::LSHIFTMOD),
+            Some(Action::DeleteCharBackward)
+        );
+        // unless the shifted key has a binding of its own
+        assert_eq!(
+            keymap.action(Keycode::Return, Mod::RSHIFTMOD),
+            Some(Action::AcceptInput)
+        );
+        assert_eq!(keymap.action(Keycode::Return, Mod::LCTRLMOD), None);
+        // lock keys don't matter
+        assert_eq!(
+            keymap.action(Keycode::Down, Mod::NUMMOD | Mod::CAPSMOD),
+            Some(Action::Next)
+        );
+    }
+
+    #[test]
+    fn later_bindings_replace_the_defaults() {
+        let keymap =
+            Keymap::new(&[String::from("ctrl+j = next"), String::from("Escape=none")]).unwrap();
+
+        assert_eq!(keymap.action(Keycode::J, Mod::LCTRLMOD), Some(Action::Next));
+        assert_eq!(keymap.action(Keycode::Escape, Mod::NOMOD), None);
+        assert_eq!(
+            keymap.action(Keycode::C, Mod::LCTRLMOD),
+            Some(Action::Cancel)
+        );
+
+        assert!(Keymap::new(&[String::from("ctrl+j")]).is_err());
+        assert!(Keymap::new(&[String::from("ctrl+j=fly")]).is_err());
+    }
+
+    #[test]
+    fn names_pressed_keys_like_bindings() {
+        assert_eq!(
+            key_name(Keycode::Return, Mod::LCTRLMOD | Mod::RSHIFTMOD),
+            "ctrl+shift+Return"
+        );
+        assert_eq!(key_name(Keycode::KpEnter, Mod::NOMOD), "Keypad-Enter");
+    }
+}
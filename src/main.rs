@@ -1,54 +1,309 @@
 use std::{
-    error::Error,
+    collections::{HashMap, HashSet},
+    env, fs, io,
+    os::unix::fs::FileTypeExt,
     process::{self, Stdio},
+    sync::mpsc,
+    thread::{self, JoinHandle},
 };
 
 #[allow(unused_imports)]
 use clap::Parser;
-use config::RunnerMenuSettings;
+use command::CommandLine;
+use config::{
+    RunnerMenuSettings, BACKGROUND_COLOR, BACKGROUND_COLOR_ACTIVE, BORDER_COLOR, BORDER_SIZE,
+    FONT_COLOR, FONT_COLOR_ACTIVE, FONT_POINT_SIZE, GRID_COLUMNS, GRID_ROWS, HIGHLIGHT_COLOR,
+    HIGHLIGHT_COLOR_ACTIVE, HISTORY_SIZE, HISTORY_WEIGHT, ICON_SIZE, LINE_SPACING,
+    MAX_ITEM_DISPLAY_COUNT, PADDING, PREFIX_BONUS, TERMINAL, WINDOW_WIDTH, WORD_BOUNDARY_BONUS,
+};
+use config_file::read_config_file;
+use desktop_entries::{get_desktop_entries, DesktopEntry};
+use dmenu::{print_selection, read_items, InputFormat};
+use error::RunnerError;
 use executables::get_executables;
+use geometry::Layout;
+use history::History;
+use item::{Item, DESKTOP_FILE_KEY};
+use keymap::{Keymap, ACTIONS};
 use runner::Runner;
+use theme::{import_colors, list_themes, load_theme};
 
 mod arguments;
+mod command;
 mod config;
+mod config_file;
+mod desktop_entries;
+mod dmenu;
+mod error;
 mod executables;
+mod geometry;
+mod history;
+mod icons;
+mod item;
+mod keymap;
 mod runner;
+mod theme;
 mod utils;
 
-fn main() -> Result<(), Box<dyn Error>> {
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("Error: {}", e);
+        process::exit(e.exit_code());
+    }
+}
+
+fn run() -> Result<(), RunnerError> {
     let args = arguments::Arguments::parse();
 
-    let executables = get_executables()?;
+    let config = read_config_file(args.config.clone()).map_err(RunnerError::Config)?;
+    let args = args.merge(config);
+
+    if args.list_themes {
+        for (name, source) in list_themes() {
+            println!("{:<26}{}", name, source);
+        }
+        return Ok(());
+    }
+
+    // imported colors only fill in the colors that aren't set individually, and the theme only
+    // the ones that are still missing after that
+    let args = match args.colors_from {
+        Some(ref source) => match import_colors(source) {
+            Ok(colors) => args.merge(colors.into()),
+            // the source may not exist yet, e.g. before pywal ran for the first time
+            Err(e) => {
+                eprintln!("Warning: {}", e);
+                args
+            }
+        },
+        None => args,
+    };
+
+    let args = match args.theme {
+        Some(ref theme) => {
+            let theme = load_theme(theme).map_err(RunnerError::Config)?;
+            args.merge(theme.into())
+        }
+        None => args,
+    };
+
+    let keymap = Keymap::new(&args.bind).map_err(RunnerError::Config)?;
+
+    if args.list_actions {
+        for (name, action, description) in ACTIONS {
+            println!(
+                "{:<26}{:<40}{}",
+                name,
+                description,
+                keymap.keys(*action).join(", ")
+            );
+        }
+        return Ok(());
+    }
+
+    let dmenu = args.dmenu || stdin_is_piped();
+    let input_format = args.input_format.unwrap_or_default();
+
+    // the items are looked for in the background so the window opens right away
+    let (sender, receiver) = mpsc::channel::<Vec<Item>>();
+
+    let mut desktop_entries_thread: Option<JoinHandle<HashMap<String, DesktopEntry>>> = None;
+
+    if dmenu {
+        let sender = sender.clone();
+        thread::spawn(move || {
+            if let Err(e) = read_items(io::stdin().lock(), input_format, &sender) {
+                eprintln!("Warning: could not read stdin: {}", e);
+            }
+        });
+    } else {
+        let executables_sender = sender.clone();
+        let (recursive, rebuild_cache) = (args.recursive.unwrap_or(false), args.rebuild_cache);
+        thread::spawn(move || {
+            let _ = executables_sender.send(get_executables(recursive, rebuild_cache));
+        });
+
+        if !args.no_desktop_entries.unwrap_or(false) {
+            let sender = sender.clone();
+            desktop_entries_thread = Some(thread::spawn(move || {
+                // entries by desktop file, only the first entry with a name is shown
+                let mut desktop_entries: HashMap<String, DesktopEntry> = HashMap::new();
+                let mut names: HashSet<String> = HashSet::new();
+                let mut items: Vec<Item> = Vec::new();
+
+                for entry in get_desktop_entries() {
+                    if names.insert(entry.name.clone()) {
+                        items.push(entry.item());
+                        desktop_entries
+                            .insert(entry.file_path.to_string_lossy().into_owned(), entry);
+                    }
+                }
+
+                let _ = sender.send(items);
+                desktop_entries
+            }));
+        }
+    }
+
+    // the menu knows that all items arrived once every sender is gone
+    drop(sender);
+
+    let mut history = if dmenu || args.no_history.unwrap_or(false) {
+        None
+    } else {
+        History::load(args.history_size.unwrap_or(HISTORY_SIZE))
+    };
+
+    let frecencies = match history {
+        Some(ref history) => history.frecencies(),
+        None => HashMap::new(),
+    };
+
+    let terminal = args.terminal.unwrap_or_else(|| match env::var("TERMINAL") {
+        Ok(terminal) if !terminal.is_empty() => format!("{} -e", terminal),
+        _ => String::from(TERMINAL),
+    });
+
+    let layout = args.layout.unwrap_or_default();
+    let font_size = args.font_size.unwrap_or(FONT_POINT_SIZE);
 
     let mut runner = Runner::new(
-        args.prompt,
-        executables,
+        args.prompt.unwrap_or_default(),
+        receiver,
+        frecencies,
         RunnerMenuSettings {
             font: args.font,
-            font_color: args.font_color,
-            font_color_active: args.font_color_active,
-            border_color: args.border_color,
-            border_size: args.border_size,
-            background_color: args.background_color,
-            background_color_active: args.background_color_active,
-            rows: args.rows,
-            font_size: args.font_size,
-            line_spacing: args.line_spacing,
+            font_color: args.font_color.unwrap_or(FONT_COLOR.into()),
+            font_color_active: args.font_color_active.unwrap_or(FONT_COLOR_ACTIVE.into()),
+            highlight_color: args.highlight_color.unwrap_or(HIGHLIGHT_COLOR.into()),
+            highlight_color_active: args
+                .highlight_color_active
+                .unwrap_or(HIGHLIGHT_COLOR_ACTIVE.into()),
+            highlight_bold: args.highlight_bold.unwrap_or(false),
+            highlight_underline: args.highlight_underline.unwrap_or(false),
+            description_color: args.description_color,
+            description_color_active: args.description_color_active,
+            border_color: args.border_color.unwrap_or(BORDER_COLOR.into()),
+            border_size: args.border_size.unwrap_or(BORDER_SIZE),
+            background_color: args.background_color.unwrap_or(BACKGROUND_COLOR.into()),
+            background_color_active: args
+                .background_color_active
+                .unwrap_or(BACKGROUND_COLOR_ACTIVE.into()),
+            layout,
+            // the grid's cells are a lot taller than rows
+            rows: args.rows.unwrap_or(match layout {
+                Layout::Grid => GRID_ROWS,
+                _ => MAX_ITEM_DISPLAY_COUNT,
+            }),
+            columns: args.columns.unwrap_or(GRID_COLUMNS).max(1),
+            icon_size: args.icon_size.unwrap_or(ICON_SIZE),
+            // lines read from stdin are arbitrary text that shouldn't be matched to icons, JSON
+            // items can name theirs
+            icons: !args.no_icons.unwrap_or(false)
+                && (!dmenu || input_format == InputFormat::Jsonl),
+            icon_theme: args.icon_theme,
+            font_size,
+            description_position: args.description_position.unwrap_or_default(),
+            // descriptions are smaller than the labels unless they're set to a size
+            description_font_size: args
+                .description_font_size
+                .unwrap_or(font_size * 3 / 4)
+                .max(1),
+            line_spacing: args.line_spacing.unwrap_or(LINE_SPACING),
             display_index: args.display,
+            width: args.width.unwrap_or(WINDOW_WIDTH),
+            padding: args.padding.unwrap_or(PADDING),
+            position: args.position.unwrap_or_default(),
+            offset: (args.x_offset.unwrap_or(0), args.y_offset.unwrap_or(0)),
+            word_boundary_bonus: args.word_boundary_bonus.unwrap_or(WORD_BOUNDARY_BONUS),
+            prefix_bonus: args.prefix_bonus.unwrap_or(PREFIX_BONUS),
+            history_weight: args.history_weight.unwrap_or(HISTORY_WEIGHT),
+            keymap,
+            keep_open: args.keep_open.unwrap_or(false),
+            // items from stdin keep their order, otherwise the most used programs come first
+            // and the rest is alphabetical
+            sort_items: !dmenu,
         },
-    );
+    )?;
 
-    if let Some(program) = runner.run() {
-        run_program(program);
+    match runner.run()? {
+        Some(selection) if dmenu => {
+            print_selection(&selection, args.output_format.unwrap_or_default())?
+        }
+        Some(selection) => {
+            let desktop_entries = desktop_entries_thread
+                .and_then(|thread| thread.join().ok())
+                .unwrap_or_default();
+
+            // the history is by label, which is what the items are matched by
+            let (launched, name) = match selection.item {
+                Some(item) => {
+                    let entry = item
+                        .metadata
+                        .get(DESKTOP_FILE_KEY)
+                        .and_then(|path| path.as_str())
+                        .and_then(|path| desktop_entries.get(path));
+
+                    let launched = match entry {
+                        Some(entry) => run_desktop_entry(entry, &terminal),
+                        None => run_program(&item.value),
+                    };
+
+                    (launched, item.label)
+                }
+                None => (run_program(&selection.query), selection.query),
+            };
+
+            if let Some(ref mut history) = history {
+                if launched {
+                    history.record(&name);
+                    let _ = history.save();
+                }
+            }
+        }
+        None if dmenu => process::exit(1),
+        None => (),
     }
 
     Ok(())
 }
 
-fn run_program(program: impl ToString) {
-    let _ = process::Command::new(program.to_string())
+fn run_program(program: impl ToString) -> bool {
+    spawn(CommandLine::parse(&program.to_string()).into_command())
+}
+
+fn run_desktop_entry(entry: &DesktopEntry, terminal: &str) -> bool {
+    match entry.command(terminal) {
+        Some(words) => {
+            let mut command = CommandLine::Words(words).into_command();
+
+            if let Some(ref working_directory) = entry.working_directory {
+                command.current_dir(working_directory);
+            }
+
+            spawn(command)
+        }
+        None => false,
+    }
+}
+
+fn spawn(mut command: process::Command) -> bool {
+    command
         .stdin(Stdio::null())
         .stdout(Stdio::null())
         .stderr(Stdio::null())
-        .spawn();
+        .spawn()
+        .is_ok()
+}
+
+// A terminal or /dev/null on stdin (e.g. when started from a keybinding) should not switch
+// to dmenu mode, only actual pipes and redirected files.
+fn stdin_is_piped() -> bool {
+    match fs::metadata("/dev/stdin") {
+        Ok(metadata) => {
+            let file_type = metadata.file_type();
+            file_type.is_fifo() || file_type.is_file()
+        }
+        Err(_) => false,
+    }
 }
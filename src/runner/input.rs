@@ -0,0 +1,283 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+// A single line of text with a cursor. Cursor positions are byte indices that always sit on
+// grapheme cluster boundaries, so an emoji or a letter with combining accents is moved over and
+// deleted as a whole. Editing methods return whether the text changed.
+#[derive(Default)]
+pub struct LineEditor {
+    text: String,
+    cursor: usize,
+    kill_buffer: String,
+}
+
+impl LineEditor {
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn set(&mut self, text: &str) {
+        self.text = text.to_string();
+        self.cursor = self.text.len();
+    }
+
+    pub fn insert(&mut self, text: &str) -> bool {
+        self.text.insert_str(self.cursor, text);
+        self.cursor += text.len();
+
+        !text.is_empty()
+    }
+
+    pub fn move_left(&mut self) {
+        self.cursor = self.previous_boundary(self.cursor);
+    }
+
+    pub fn move_right(&mut self) {
+        self.cursor = self.next_boundary(self.cursor);
+    }
+
+    pub fn move_word_left(&mut self) {
+        self.cursor = self.previous_word_start(is_word);
+    }
+
+    pub fn move_word_right(&mut self) {
+        let graphemes = self.text[self.cursor..].grapheme_indices(true);
+        let mut end = self.text.len();
+        let mut in_word = false;
+
+        for (index, grapheme) in graphemes {
+            if is_word(grapheme) {
+                in_word = true;
+            } else if in_word {
+                end = self.cursor + index;
+                break;
+            }
+        }
+
+        self.cursor = end;
+    }
+
+    pub fn move_home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn move_end(&mut self) {
+        self.cursor = self.text.len();
+    }
+
+    pub fn delete_backward(&mut self) -> bool {
+        let start = self.previous_boundary(self.cursor);
+        self.delete(start, self.cursor, false)
+    }
+
+    pub fn delete_forward(&mut self) -> bool {
+        let end = self.next_boundary(self.cursor);
+        self.delete(self.cursor, end, false)
+    }
+
+    // Deletes up to the previous whitespace like the shell's ctrl+w
+    pub fn delete_word_backward(&mut self) -> bool {
+        let start = self.previous_word_start(|g| !g.trim().is_empty());
+        self.delete(start, self.cursor, true)
+    }
+
+    // Deletes the previous alphanumeric word like readline's alt+backspace
+    pub fn delete_alphanumeric_word_backward(&mut self) -> bool {
+        let start = self.previous_word_start(is_word);
+        self.delete(start, self.cursor, true)
+    }
+
+    pub fn kill_to_start(&mut self) -> bool {
+        self.delete(0, self.cursor, true)
+    }
+
+    pub fn kill_to_end(&mut self) -> bool {
+        self.delete(self.cursor, self.text.len(), true)
+    }
+
+    pub fn yank(&mut self) -> bool {
+        let kill_buffer = self.kill_buffer.clone();
+        self.insert(&kill_buffer)
+    }
+
+    fn delete(&mut self, start: usize, end: usize, kill: bool) -> bool {
+        if start >= end {
+            return false;
+        }
+
+        let deleted: String = self.text.drain(start..end).collect();
+        if kill {
+            self.kill_buffer = deleted;
+        }
+        self.cursor = start;
+
+        true
+    }
+
+    fn previous_boundary(&self, position: usize) -> usize {
+        self.text[..position]
+            .grapheme_indices(true)
+            .next_back()
+            .map(|(index, _)| index)
+            .unwrap_or(0)
+    }
+
+    fn next_boundary(&self, position: usize) -> usize {
+        self.text[position..]
+            .graphemes(true)
+            .next()
+            .map(|grapheme| position + grapheme.len())
+            .unwrap_or(position)
+    }
+
+    // The start of the word before the cursor, skipping anything that isn't part of a word first
+    fn previous_word_start(&self, is_word: impl Fn(&str) -> bool) -> usize {
+        let mut start = 0;
+        let mut in_word = false;
+
+        for (index, grapheme) in self.text[..self.cursor].grapheme_indices(true).rev() {
+            if is_word(grapheme) {
+                in_word = true;
+            } else if in_word {
+                start = index + grapheme.len();
+                break;
+            }
+        }
+
+        start
+    }
+}
+
+fn is_word(grapheme: &str) -> bool {
+    grapheme.chars().any(char::is_alphanumeric)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn editor(text: &str) -> LineEditor {
+        let mut editor = LineEditor::default();
+        editor.set(text);
+        editor
+    }
+
+    #[test]
+    fn deletes_whole_graphemes() {
+        // an e with a combining acute accent and a family emoji joined with zero width joiners
+        let mut line = editor("ae\u{301}\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}");
+
+        assert!(line.delete_backward());
+        assert_eq!(line.as_str(), "ae\u{301}");
+        assert!(line.delete_backward());
+        assert_eq!(line.as_str(), "a");
+
+        line.set("e\u{301}b");
+        line.move_home();
+        assert!(line.delete_forward());
+        assert_eq!(line.as_str(), "b");
+        assert_eq!(line.cursor(), 0);
+    }
+
+    #[test]
+    fn does_nothing_at_the_ends() {
+        let mut line = editor("");
+        assert!(!line.delete_backward());
+        assert!(!line.delete_word_backward());
+
+        line.set("abc");
+        assert!(!line.delete_forward());
+        assert!(!line.kill_to_end());
+        line.move_home();
+        assert!(!line.delete_backward());
+        assert!(!line.kill_to_start());
+        assert_eq!(line.as_str(), "abc");
+    }
+
+    #[test]
+    fn moves_over_graphemes_and_words() {
+        let mut line = editor("ñ\u{303} foo-bar");
+
+        line.move_home();
+        line.move_right();
+        assert_eq!(line.cursor(), "ñ\u{303}".len());
+        line.move_left();
+        assert_eq!(line.cursor(), 0);
+
+        line.move_word_right();
+        assert_eq!(line.cursor(), "ñ\u{303}".len());
+        line.move_word_right();
+        assert_eq!(line.cursor(), "ñ\u{303} foo".len());
+        line.move_word_right();
+        assert_eq!(line.cursor(), line.as_str().len());
+
+        line.move_word_left();
+        assert_eq!(line.cursor(), "ñ\u{303} foo-".len());
+        line.move_word_left();
+        assert_eq!(line.cursor(), "ñ\u{303} ".len());
+    }
+
+    #[test]
+    fn inserts_at_the_cursor() {
+        let mut line = editor("ac");
+
+        line.move_left();
+        assert!(line.insert("b"));
+        assert!(!line.insert(""));
+        assert_eq!(line.as_str(), "abc");
+        assert_eq!(line.cursor(), 2);
+    }
+
+    #[test]
+    fn deletes_words_backward() {
+        let mut line = editor("git commit --amend  ");
+        assert!(line.delete_word_backward());
+        assert_eq!(line.as_str(), "git commit ");
+
+        let mut line = editor("foo-bar baz/qux ");
+        assert!(line.delete_alphanumeric_word_backward());
+        assert_eq!(line.as_str(), "foo-bar baz/");
+        assert!(line.delete_alphanumeric_word_backward());
+        assert_eq!(line.as_str(), "foo-bar ");
+    }
+
+    #[test]
+    fn yanks_the_last_killed_text() {
+        let mut line = editor("hello world");
+
+        assert!(line.delete_word_backward());
+        // deleting single graphemes doesn't replace the kill buffer
+        assert!(line.delete_backward());
+        line.move_home();
+        assert!(line.yank());
+        assert_eq!(line.as_str(), "worldhello");
+
+        assert!(line.kill_to_end());
+        assert_eq!(line.as_str(), "world");
+        line.move_home();
+        assert!(line.yank());
+        assert_eq!(line.as_str(), "helloworld");
+
+        line.move_end();
+        line.move_left();
+        assert!(line.kill_to_start());
+        assert_eq!(line.as_str(), "d");
+        assert_eq!(line.cursor(), 0);
+        assert!(line.yank());
+        assert_eq!(line.as_str(), "helloworld");
+    }
+
+    #[test]
+    fn yanking_nothing_does_nothing() {
+        let mut line = editor("abc");
+        assert!(!line.yank());
+        assert_eq!(line.as_str(), "abc");
+    }
+}
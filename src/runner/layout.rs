@@ -0,0 +1,846 @@
+use std::{collections::HashMap, hash::Hash};
+
+use sdl2::{
+    pixels::{Color, PixelFormatEnum},
+    rect::Rect,
+    render::{Texture, TextureCreator, WindowCanvas},
+    surface::Surface,
+    ttf::Font,
+    video::WindowContext,
+};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{
+    config::RunnerMenuSettings,
+    geometry::{DescriptionPosition, Layout},
+    icons::{render_icon, IconLookup},
+    item::{Item, Source},
+};
+
+use super::matcher::Matcher;
+
+pub struct Fonts<'ttf> {
+    pub label: Font<'ttf, 'static>,
+    // for the matched characters
+    pub highlight: Font<'ttf, 'static>,
+    pub description: Font<'ttf, 'static>,
+    // for the initials drawn in place of icons
+    pub initial: Font<'ttf, 'static>,
+}
+
+pub struct Colors {
+    pub font: Color,
+    pub font_active: Color,
+    pub highlight: Color,
+    pub highlight_active: Color,
+    pub background_active: Color,
+    pub description: Color,
+    pub description_active: Color,
+    pub border: Color,
+}
+
+// The textures items are drawn with, rendered the first time they're shown
+pub struct ItemTextures<'r> {
+    creator: &'r TextureCreator<WindowContext>,
+    // by text, active state and matched characters
+    labels: FrameCache<(String, bool, Vec<usize>), Vec<Texture<'r>>>,
+    // by text and active state
+    descriptions: FrameCache<(String, bool), Option<Texture<'r>>>,
+    // by icon name and size, `None` if the item has no icon
+    icons: FrameCache<(String, u32), Option<Texture<'r>>>,
+    initials: FrameCache<String, Option<Texture<'r>>>,
+    // the widths of labels in the horizontal layout
+    label_widths: FrameCache<String, u32>,
+}
+
+impl<'r> ItemTextures<'r> {
+    pub fn new(creator: &'r TextureCreator<WindowContext>) -> Self {
+        Self {
+            creator,
+            labels: FrameCache::default(),
+            descriptions: FrameCache::default(),
+            icons: FrameCache::default(),
+            initials: FrameCache::default(),
+            label_widths: FrameCache::default(),
+        }
+    }
+
+    // Drops what wasn't drawn in the last frame
+    pub fn evict_unused(&mut self) {
+        self.labels.evict_unused();
+        self.descriptions.evict_unused();
+        self.icons.evict_unused();
+        self.initials.evict_unused();
+        self.label_widths.evict_unused();
+    }
+
+    // The label as a texture per run of matched and unmatched characters
+    fn label(
+        &mut self,
+        fonts: &Fonts,
+        colors: &Colors,
+        label: &str,
+        active: bool,
+        matched_indices: Vec<usize>,
+    ) -> &[Texture<'r>] {
+        let creator = self.creator;
+
+        self.labels.get_or_insert_with(
+            (label.to_string(), active, matched_indices),
+            |(label, active, matched_indices)| {
+                split_matches(label, matched_indices)
+                    .into_iter()
+                    // runs that fail to render are left out instead of aborting
+                    .filter_map(|(text, matched)| {
+                        let surface = if matched {
+                            &fonts.highlight
+                        } else {
+                            &fonts.label
+                        }
+                        .render(text)
+                        .blended(match (matched, *active) {
+                            (false, false) => colors.font,
+                            (false, true) => colors.font_active,
+                            (true, false) => colors.highlight,
+                            (true, true) => colors.highlight_active,
+                        })
+                        .ok()?;
+
+                        creator.create_texture_from_surface(surface).ok()
+                    })
+                    .collect()
+            },
+        )
+    }
+
+    fn label_width(&mut self, fonts: &Fonts, label: &str) -> u32 {
+        *self
+            .label_widths
+            .get_or_insert_with(label.to_string(), |label| {
+                fonts
+                    .label
+                    .size_of(label)
+                    .map(|(width, _)| width)
+                    .unwrap_or(0)
+            })
+    }
+
+    fn description(
+        &mut self,
+        fonts: &Fonts,
+        colors: &Colors,
+        item: &Item,
+        active: bool,
+    ) -> Option<&Texture<'r>> {
+        let description = item.description.as_ref()?;
+        let creator = self.creator;
+
+        self.descriptions
+            .get_or_insert_with((description.clone(), active), |_| {
+                let color = if active {
+                    colors.description_active
+                } else {
+                    colors.description
+                };
+                let surface = fonts.description.render(description).blended(color).ok()?;
+
+                creator.create_texture_from_surface(surface).ok()
+            })
+            .as_ref()
+    }
+
+    // The item's icon, looked up and rendered the first time it's shown at a size
+    fn icon(
+        &mut self,
+        icon_lookup: &mut Option<IconLookup>,
+        item: &Item,
+        size: u32,
+    ) -> Option<&Texture<'r>> {
+        let icon = match item.icon {
+            Some(ref icon) => icon,
+            // lines from stdin are arbitrary text
+            None if item.source == Source::Stdin => return None,
+            None => &item.label,
+        };
+        let creator = self.creator;
+
+        self.icons
+            .get_or_insert_with((icon.to_string(), size), |_| {
+                let path = icon_lookup.as_mut()?.find(icon, size)?;
+                let pixmap = render_icon(&path, size)?;
+
+                // SDL wants straight alpha, tiny-skia premultiplies it
+                let mut pixels: Vec<u8> = pixmap
+                    .pixels()
+                    .iter()
+                    .flat_map(|pixel| {
+                        let color = pixel.demultiply();
+                        [color.red(), color.green(), color.blue(), color.alpha()]
+                    })
+                    .collect();
+
+                let surface =
+                    Surface::from_data(&mut pixels, size, size, size * 4, PixelFormatEnum::RGBA32)
+                        .ok()?;
+
+                creator.create_texture_from_surface(surface).ok()
+            })
+            .as_ref()
+    }
+
+    fn initial(&mut self, fonts: &Fonts, colors: &Colors, label: &str) -> Option<&Texture<'r>> {
+        let initial = label
+            .graphemes(true)
+            .next()
+            .unwrap_or_default()
+            .to_uppercase();
+        let creator = self.creator;
+
+        self.initials
+            .get_or_insert_with(initial, |initial| {
+                let surface = fonts.initial.render(initial).blended(colors.font).ok()?;
+                creator.create_texture_from_surface(surface).ok()
+            })
+            .as_ref()
+    }
+}
+
+// Rendered textures and measurements by what they were made from. Entries that weren't used
+// while drawing a frame are dropped after it, so paging through a long list only keeps what's
+// on screen instead of a texture for every item ever shown.
+struct FrameCache<K, V> {
+    // the values and whether they were used since the last eviction
+    entries: HashMap<K, (V, bool)>,
+}
+
+impl<K, V> Default for FrameCache<K, V> {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::new(),
+        }
+    }
+}
+
+impl<K: Eq + Hash, V> FrameCache<K, V> {
+    fn get_or_insert_with(&mut self, key: K, create: impl FnOnce(&K) -> V) -> &mut V {
+        let (value, used) = self
+            .entries
+            .entry(key)
+            .or_insert_with_key(|key| (create(key), true));
+        *used = true;
+
+        value
+    }
+
+    fn evict_unused(&mut self) {
+        self.entries.retain(|_, (_, used)| std::mem::take(used));
+    }
+}
+
+// The part of the items that's shown, kept across frames
+pub struct View {
+    // the first visible item
+    start: u16,
+    // the amount of items that fit in the window, for paging
+    pub page_size: u16,
+    // where the visible items were last drawn, for the mouse
+    item_rects: Vec<(Rect, u16)>,
+    // rows only make room for icons once one was found, and keep it from then on so the text
+    // doesn't move around while scrolling
+    icon_found: bool,
+}
+
+impl View {
+    pub fn new(page_size: u16) -> Self {
+        Self {
+            start: 0,
+            page_size,
+            item_rects: Vec::new(),
+            icon_found: false,
+        }
+    }
+
+    // The index of the item drawn at a position in the window
+    pub fn item_at(&self, x: i32, y: i32) -> Option<u16> {
+        self.item_rects
+            .iter()
+            .find(|(rect, _)| rect.contains_point((x, y)))
+            .map(|(_, index)| *index)
+    }
+}
+
+// Everything a layout draws the visible items with
+pub struct Frame<'a, 'ttf, 'r> {
+    pub canvas: &'a mut WindowCanvas,
+    pub textures: &'a mut ItemTextures<'r>,
+    pub icon_lookup: &'a mut Option<IconLookup>,
+    pub fonts: &'a Fonts<'ttf>,
+    pub colors: &'a Colors,
+    pub settings: &'a RunnerMenuSettings,
+    pub window_size: (u32, u32),
+    pub items: &'a [Item],
+    // indices into the items
+    pub filtered: &'a [usize],
+    pub selection_index: u16,
+    // whether the visible items should follow the selection, which they don't when the
+    // selection is moved by hovering so the rows don't move away under the cursor
+    pub follow_selection: bool,
+    pub matcher: &'a Matcher,
+    pub query: &'a str,
+    // the line with the input, which the horizontal layout puts the items on after the prompt
+    // and the input and before the loading text
+    pub input_y: i32,
+    pub input_width: u32,
+    pub loading_width: i32,
+}
+
+impl<'a> Frame<'a, '_, '_> {
+    pub fn draw(mut self, view: &mut View) {
+        view.item_rects.clear();
+
+        match self.settings.layout {
+            Layout::Vertical => self.draw_list(view),
+            Layout::Grid => self.draw_grid(view),
+            Layout::Horizontal => self.draw_line(view),
+        }
+    }
+
+    fn draw_list(&mut self, view: &mut View) {
+        let settings = self.settings;
+        let rows = settings.rows;
+        let len = self.filtered.len() as u16;
+
+        // try to keep the selection centered
+        if self.follow_selection {
+            view.start = self
+                .selection_index
+                .saturating_sub(rows.div_euclid(2))
+                .min(len.saturating_sub(rows));
+        }
+        let end = (view.start + rows).min(len);
+        view.page_size = rows;
+
+        let border_size = settings.border_size as u16;
+        let font_height = self.fonts.label.height() as u16;
+        let row_height = row_height(self.text_heights(), settings) as u16;
+        let half_line_spacing = settings.line_spacing.div_euclid(2);
+
+        if self.icon_lookup.is_some() && !view.icon_found {
+            view.icon_found = (view.start..end).any(|i| {
+                let item = self.item(i);
+                self.textures
+                    .icon(self.icon_lookup, item, row_height as u32)
+                    .is_some()
+            });
+        }
+
+        for (row, i) in (view.start..end).enumerate() {
+            let offset = border_size
+                + settings.padding * 2
+                + (font_height + settings.line_spacing)
+                + (row_height + settings.line_spacing) * row as u16;
+
+            let background_rect = Rect::new(
+                settings.border_size.into(),
+                (offset - half_line_spacing).into(),
+                self.window_size.0.saturating_sub(border_size as u32 * 2),
+                row_height as u32 + settings.line_spacing as u32,
+            );
+            self.draw_background(view, background_rect, i);
+
+            let item = self.item(i);
+            let active = i == self.selection_index;
+
+            let mut text_x = (border_size + settings.padding) as i32;
+
+            // the text of all rows lines up whether they have an icon or not
+            if view.icon_found {
+                let icon_size = row_height as u32;
+                self.draw_icon(item, Rect::new(text_x, offset.into(), icon_size, icon_size));
+
+                text_x += (icon_size + settings.padding as u32 / 2) as i32;
+            }
+
+            // a description to the right is centered on the label, one below it goes under the
+            // label
+            let label_y = match settings.description_position {
+                DescriptionPosition::Below => offset,
+                _ => offset + (row_height - font_height) / 2,
+            } as i32;
+
+            let label_end = self.draw_label(item, active, |_| (text_x, label_y));
+
+            match settings.description_position {
+                DescriptionPosition::Below => self.draw_description(item, active, |_, _| {
+                    Some((text_x, label_y + font_height as i32))
+                }),
+                DescriptionPosition::Right => {
+                    let right = self.window_size.0 as i32 - (border_size + settings.padding) as i32;
+
+                    self.draw_description(item, active, |width, height| {
+                        let x = right - width as i32;
+
+                        // descriptions next to long labels are left out, they'd overlap
+                        (x >= label_end + settings.padding as i32)
+                            .then_some((x, offset as i32 + (row_height as i32 - height as i32) / 2))
+                    });
+                }
+                DescriptionPosition::Hidden => (),
+            }
+        }
+    }
+
+    fn draw_grid(&mut self, view: &mut View) {
+        let settings = self.settings;
+        let columns = settings.columns;
+        let rows = settings.rows;
+        let len = self.filtered.len() as u16;
+
+        let border_size = settings.border_size as u32;
+        let padding = settings.padding as i32;
+        let icon_size = settings.icon_size as u32;
+        let font_height = self.fonts.label.height();
+
+        let grid_top =
+            border_size as i32 + padding * 2 + (font_height + settings.line_spacing as i32)
+                - settings.line_spacing.div_euclid(2) as i32;
+        let cell_width = self.window_size.0.saturating_sub(border_size * 2) / columns as u32;
+        let cell_height = grid_cell_height(self.text_heights(), settings);
+
+        view.page_size = columns * rows;
+
+        // scroll by whole rows to keep the selection visible
+        if self.follow_selection {
+            let selected_row = self.selection_index / columns;
+            let start_row = view.start / columns;
+
+            if selected_row < start_row {
+                view.start = selected_row * columns;
+            } else if selected_row >= start_row + rows {
+                view.start = (selected_row + 1 - rows) * columns;
+            }
+        }
+        let end = (view.start + view.page_size).min(len);
+
+        for i in view.start..end {
+            let cell = i - view.start;
+
+            let cell_rect = Rect::new(
+                border_size as i32 + (cell % columns) as i32 * cell_width as i32,
+                grid_top + (cell / columns) as i32 * cell_height as i32,
+                cell_width,
+                cell_height,
+            );
+            self.draw_background(view, cell_rect, i);
+
+            let item = self.item(i);
+            let active = i == self.selection_index;
+
+            let icon_rect = Rect::new(
+                cell_rect.x() + (cell_width as i32 - icon_size as i32) / 2,
+                cell_rect.y() + padding,
+                icon_size,
+                icon_size,
+            );
+
+            if !self.draw_icon(item, icon_rect) {
+                self.draw_initial(item, icon_rect);
+            }
+
+            // centered under the icon, long names are cut off at the cell
+            let centered =
+                |width: u32| cell_rect.x() + ((cell_width as i32 - width as i32) / 2).max(padding);
+            let text_y = icon_rect.bottom() + padding / 2;
+
+            self.canvas.set_clip_rect(Rect::new(
+                cell_rect.x() + padding,
+                cell_rect.y(),
+                (cell_width as i32 - padding * 2).max(1) as u32,
+                cell_height,
+            ));
+
+            self.draw_label(item, active, |width| (centered(width), text_y));
+
+            // there's no room for descriptions next to the names
+            if settings.description_position == DescriptionPosition::Below {
+                self.draw_description(item, active, |width, _| {
+                    Some((centered(width), text_y + font_height))
+                });
+            }
+
+            self.canvas.set_clip_rect(None);
+        }
+    }
+
+    fn draw_line(&mut self, view: &mut View) {
+        let settings = self.settings;
+        let len = self.filtered.len() as u16;
+        let (window_width, window_height) = self.window_size;
+
+        let padding = settings.padding as i32;
+        let inner_left = settings.border_size as i32 + padding;
+        let inner_right = window_width as i32 - inner_left;
+        let line_y = self.input_y;
+
+        // the items start a third into the window unless the input is longer
+        let items_left =
+            inner_left + (window_width as i32 / 3).max(self.input_width as i32 + padding * 2);
+
+        let (left_arrow_width, _) = self.fonts.label.size_of("<").unwrap_or((0, 0));
+        let (right_arrow_width, _) = self.fonts.label.size_of(">").unwrap_or((0, 0));
+
+        let items_start = items_left + left_arrow_width as i32 + padding;
+        let items_end = inner_right - right_arrow_width as i32 - padding - self.loading_width;
+
+        let (items, filtered, fonts) = (self.items, self.filtered, self.fonts);
+        let (page_start, page_end) = horizontal_page(
+            |i| {
+                let label = &items[filtered[i as usize]].label;
+                self.textures.label_width(fonts, label) + padding as u32 * 2
+            },
+            len,
+            self.selection_index,
+            (items_end - items_start).max(0) as u32,
+        );
+        view.page_size = (page_end - page_start).max(1);
+
+        if page_start > 0 {
+            self.draw_arrow("<", items_left);
+        }
+
+        let mut x = items_start;
+
+        for i in page_start..page_end {
+            let item = self.item(i);
+            let width = self.textures.label_width(self.fonts, &item.label) + padding as u32 * 2;
+
+            let background_rect = Rect::new(
+                x,
+                settings.border_size.into(),
+                width,
+                window_height.saturating_sub(settings.border_size as u32 * 2),
+            );
+            self.draw_background(view, background_rect, i);
+
+            self.draw_label(item, i == self.selection_index, |_| (x + padding, line_y));
+
+            x += width as i32;
+        }
+
+        if page_end < len {
+            self.draw_arrow(
+                ">",
+                inner_right - right_arrow_width as i32 - self.loading_width,
+            );
+        }
+    }
+
+    fn item(&self, i: u16) -> &'a Item {
+        &self.items[self.filtered[i as usize]]
+    }
+
+    fn text_heights(&self) -> (u32, u32) {
+        (
+            self.fonts.label.height() as u32,
+            self.fonts.description.height() as u32,
+        )
+    }
+
+    // Remembers where the item is for the mouse and fills the background if it's selected.
+    // Other items already have the background, drawing it again would make a translucent
+    // background darker.
+    fn draw_background(&mut self, view: &mut View, rect: Rect, i: u16) {
+        if i == self.selection_index {
+            self.canvas.set_draw_color(self.colors.background_active);
+            let _ = self.canvas.fill_rect(rect);
+        }
+        view.item_rects.push((rect, i));
+    }
+
+    // Draws the label with the matched characters highlighted where `position` puts it given
+    // its width, and returns where it ends
+    fn draw_label(
+        &mut self,
+        item: &Item,
+        active: bool,
+        position: impl FnOnce(u32) -> (i32, i32),
+    ) -> i32 {
+        let matched_indices = self.matcher.indices(&item.label, self.query);
+        let textures = self.textures.label(
+            self.fonts,
+            self.colors,
+            &item.label,
+            active,
+            matched_indices,
+        );
+
+        let (x, y) = position(textures.iter().map(|texture| texture.query().width).sum());
+        copy_textures(self.canvas, textures, x, y)
+    }
+
+    // Draws the description, if the item has one, where `position` puts it given its size
+    fn draw_description(
+        &mut self,
+        item: &Item,
+        active: bool,
+        position: impl FnOnce(u32, u32) -> Option<(i32, i32)>,
+    ) {
+        let Some(texture) = self
+            .textures
+            .description(self.fonts, self.colors, item, active)
+        else {
+            return;
+        };
+
+        let query = texture.query();
+        if let Some((x, y)) = position(query.width, query.height) {
+            let _ = self.canvas.copy(
+                texture,
+                None,
+                Some(Rect::new(x, y, query.width, query.height)),
+            );
+        }
+    }
+
+    // Returns whether the item has an icon
+    fn draw_icon(&mut self, item: &Item, rect: Rect) -> bool {
+        match self.textures.icon(self.icon_lookup, item, rect.width()) {
+            Some(icon) => {
+                let _ = self.canvas.copy(icon, None, Some(rect));
+                true
+            }
+            None => false,
+        }
+    }
+
+    // A placeholder with the item's initial for items without an icon
+    fn draw_initial(&mut self, item: &Item, rect: Rect) {
+        self.canvas.set_draw_color(self.colors.border);
+        let _ = self.canvas.fill_rect(rect);
+
+        if let Some(texture) = self.textures.initial(self.fonts, self.colors, &item.label) {
+            let query = texture.query();
+            let initial_rect = Rect::from_center(rect.center(), query.width, query.height);
+            let _ = self.canvas.copy(texture, None, Some(initial_rect));
+        }
+    }
+
+    fn draw_arrow(&mut self, arrow: &str, x: i32) {
+        draw_text(
+            self.canvas,
+            self.textures.creator,
+            &self.fonts.label,
+            arrow,
+            self.colors.font,
+            (x, self.input_y),
+        );
+    }
+}
+
+// A label, with the description next to or under it
+pub fn row_height(
+    (font_height, description_height): (u32, u32),
+    settings: &RunnerMenuSettings,
+) -> u32 {
+    match settings.description_position {
+        DescriptionPosition::Right => font_height.max(description_height),
+        DescriptionPosition::Below => font_height + description_height,
+        DescriptionPosition::Hidden => font_height,
+    }
+}
+
+// An icon with the name under it, and the description under that if it goes there. There's
+// no room for descriptions next to the names.
+pub fn grid_cell_height(
+    (font_height, description_height): (u32, u32),
+    settings: &RunnerMenuSettings,
+) -> u32 {
+    let padding = settings.padding as u32;
+    let text_height = match settings.description_position {
+        DescriptionPosition::Below => font_height + description_height,
+        DescriptionPosition::Right | DescriptionPosition::Hidden => font_height,
+    };
+
+    padding + settings.icon_size as u32 + padding / 2 + text_height + padding
+}
+
+pub fn draw_text(
+    canvas: &mut WindowCanvas,
+    creator: &TextureCreator<WindowContext>,
+    font: &Font,
+    text: &str,
+    color: Color,
+    (x, y): (i32, i32),
+) {
+    if let Ok(texture) = font
+        .render(text)
+        .blended(color)
+        .map_err(|e| e.to_string())
+        .and_then(|surface| {
+            creator
+                .create_texture_from_surface(surface)
+                .map_err(|e| e.to_string())
+        })
+    {
+        copy_textures(canvas, &[texture], x, y);
+    }
+}
+
+// Draws the textures next to each other starting at x and returns where they end
+fn copy_textures(canvas: &mut WindowCanvas, textures: &[Texture], x: i32, y: i32) -> i32 {
+    let mut x = x;
+
+    for texture in textures {
+        let query = texture.query();
+
+        let _ = canvas.copy(
+            texture,
+            None,
+            Some(Rect::new(x, y, query.width, query.height)),
+        );
+
+        x += query.width as i32;
+    }
+
+    x
+}
+
+// Splits the items into pages of items that fit next to each other in the available width and
+// returns the start and end of the page with the selection. Every page has at least one item.
+fn horizontal_page(
+    mut item_width: impl FnMut(u16) -> u32,
+    len: u16,
+    selection_index: u16,
+    available_width: u32,
+) -> (u16, u16) {
+    let mut page_start = 0;
+
+    loop {
+        let mut page_end = page_start;
+        let mut used_width = 0;
+
+        while page_end < len {
+            let width = item_width(page_end);
+            if page_end > page_start && used_width + width > available_width {
+                break;
+            }
+            used_width += width;
+            page_end += 1;
+        }
+
+        if selection_index < page_end || page_end >= len {
+            return (page_start, page_end);
+        }
+
+        page_start = page_end;
+    }
+}
+
+// Splits the text into runs of matched and unmatched characters. The indices are char indices.
+fn split_matches<'a>(text: &'a str, matched_indices: &[usize]) -> Vec<(&'a str, bool)> {
+    let mut runs: Vec<(&'a str, bool)> = Vec::new();
+    let mut run_start = 0;
+    let mut run_matched = false;
+
+    for (char_index, (byte_index, _)) in text.char_indices().enumerate() {
+        let matched = matched_indices.contains(&char_index);
+
+        if matched != run_matched && byte_index > run_start {
+            runs.push((&text[run_start..byte_index], run_matched));
+            run_start = byte_index;
+        }
+        run_matched = matched;
+    }
+
+    if run_start < text.len() {
+        runs.push((&text[run_start..], run_matched));
+    }
+
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{HISTORY_WEIGHT, PREFIX_BONUS, WORD_BOUNDARY_BONUS};
+
+    #[test]
+    fn splits_text_into_matched_runs() {
+        assert_eq!(
+            split_matches("firefox", &[0, 1, 4]),
+            [("fi", true), ("re", false), ("f", true), ("ox", false)]
+        );
+        assert_eq!(split_matches("abc", &[]), [("abc", false)]);
+        assert_eq!(split_matches("abc", &[0, 1, 2]), [("abc", true)]);
+        assert!(split_matches("", &[]).is_empty());
+    }
+
+    #[test]
+    fn splits_multi_byte_text_on_char_boundaries() {
+        assert_eq!(
+            split_matches("héllo wörld", &[1, 2, 7]),
+            [
+                ("h", false),
+                ("él", true),
+                ("lo w", false),
+                ("ö", true),
+                ("rld", false)
+            ]
+        );
+
+        // the matcher's indices are char indices as well
+        let matcher = Matcher::new(
+            WORD_BOUNDARY_BONUS,
+            PREFIX_BONUS,
+            HISTORY_WEIGHT,
+            HashMap::new(),
+        );
+        let label = "日本語-エディタ";
+        assert_eq!(
+            split_matches(label, &matcher.indices(label, "本エ")),
+            [
+                ("日", false),
+                ("本", true),
+                ("語-", false),
+                ("エ", true),
+                ("ディタ", false)
+            ]
+        );
+    }
+
+    fn page(widths: &[u32], selection_index: u16, available_width: u32) -> (u16, u16) {
+        horizontal_page(
+            |i| widths[i as usize],
+            widths.len() as u16,
+            selection_index,
+            available_width,
+        )
+    }
+
+    #[test]
+    fn pages_hold_the_items_that_fit() {
+        let widths = [10; 8];
+
+        assert_eq!(page(&widths, 0, 35), (0, 3));
+        assert_eq!(page(&widths, 2, 35), (0, 3));
+        assert_eq!(page(&widths, 3, 35), (3, 6));
+        assert_eq!(page(&widths, 7, 35), (6, 8));
+        assert_eq!(page(&widths, 0, 30), (0, 3));
+    }
+
+    #[test]
+    fn items_wider_than_the_page_get_one_to_themselves() {
+        let widths = [10, 100, 10, 10];
+
+        assert_eq!(page(&widths, 0, 50), (0, 1));
+        assert_eq!(page(&widths, 1, 50), (1, 2));
+        assert_eq!(page(&widths, 2, 50), (2, 4));
+    }
+
+    #[test]
+    fn shows_an_item_per_page_without_room() {
+        assert_eq!(page(&[10, 10, 10], 1, 0), (1, 2));
+        assert_eq!(page(&[], 0, 0), (0, 0));
+        assert_eq!(page(&[], 0, 100), (0, 0));
+    }
+}
@@ -0,0 +1,207 @@
+use std::{cmp::Ordering, collections::HashMap};
+
+use fuzzy_matcher::{
+    skim::{SkimMatcherV2, SkimScoreConfig},
+    FuzzyMatcher,
+};
+
+use crate::item::Item;
+
+pub struct Matcher {
+    skim: SkimMatcherV2,
+    prefix_bonus: f64,
+    history_weight: f64,
+    frecencies: HashMap<String, f64>,
+}
+
+impl Matcher {
+    pub fn new(
+        word_boundary_bonus: i32,
+        prefix_bonus: i32,
+        history_weight: f64,
+        frecencies: HashMap<String, f64>,
+    ) -> Self {
+        let default_config = SkimScoreConfig::default();
+
+        // word boundaries, separators and camel case humps are weighted relative to each other
+        // like in skim's default configuration
+        let bonus_head = word_boundary_bonus;
+        let score_config = SkimScoreConfig {
+            bonus_head,
+            bonus_break: bonus_head + default_config.gap_extension,
+            bonus_camel: bonus_head + 2 * default_config.gap_extension,
+            ..default_config
+        };
+
+        Self {
+            skim: SkimMatcherV2::default().score_config(score_config),
+            prefix_bonus: prefix_bonus.into(),
+            history_weight,
+            frecencies,
+        }
+    }
+
+    // The indices of the matching items, best match first
+    pub fn filter(&self, input: &str, items: &[Item]) -> Vec<usize> {
+        // without a query the original order (which is sorted by frecency) is kept
+        if input.is_empty() {
+            return (0..items.len()).collect();
+        }
+
+        let mut matches: Vec<(f64, usize)> = items
+            .iter()
+            .enumerate()
+            .filter_map(|(i, item)| Some((self.score(item, input)?, i)))
+            .collect();
+
+        matches.sort_by(|(a_score, a), (b_score, b)| {
+            b_score
+                .total_cmp(a_score)
+                .then_with(|| compare_ties(&items[*a].label, &items[*b].label, input))
+        });
+
+        matches.into_iter().map(|(_, i)| i).collect()
+    }
+
+    // Puts the most used items first, keeping the order of the rest
+    pub fn sort_by_frecency(&self, items: &mut [Item]) {
+        items.sort_by(|a, b| {
+            let a = self.frecencies.get(&a.label).unwrap_or(&0.0);
+            let b = self.frecencies.get(&b.label).unwrap_or(&0.0);
+            b.total_cmp(a)
+        });
+    }
+
+    pub fn indices(&self, executable: &str, input: &str) -> Vec<usize> {
+        match self.skim.fuzzy_indices(executable, input) {
+            Some((_, indices)) => indices,
+            None => Vec::new(),
+        }
+    }
+
+    fn score(&self, item: &Item, input: &str) -> Option<f64> {
+        // keywords only count when the label doesn't match, and without the prefix bonus
+        let mut score = match self.skim.fuzzy_match(&item.label, input) {
+            Some(score) if item.label.starts_with(input) => score as f64 + self.prefix_bonus,
+            Some(score) => score as f64,
+            None => item
+                .keywords
+                .iter()
+                .filter_map(|keyword| self.skim.fuzzy_match(keyword, input))
+                .max()? as f64,
+        };
+
+        if let Some(frecency) = self.frecencies.get(&item.label) {
+            score += frecency * self.history_weight;
+        }
+
+        Some(score)
+    }
+}
+
+// Prefix matches, then exact matches, then shorter names, then alphabetical order
+fn compare_ties(a: &str, b: &str, input: &str) -> Ordering {
+    b.starts_with(input)
+        .cmp(&a.starts_with(input))
+        .then_with(|| (b == input).cmp(&(a == input)))
+        .then_with(|| a.chars().count().cmp(&b.chars().count()))
+        .then_with(|| a.to_lowercase().cmp(&b.to_lowercase()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::{HISTORY_WEIGHT, PREFIX_BONUS, WORD_BOUNDARY_BONUS},
+        item::Source,
+    };
+
+    fn matcher(frecencies: &[(&str, f64)]) -> Matcher {
+        Matcher::new(
+            WORD_BOUNDARY_BONUS,
+            PREFIX_BONUS,
+            HISTORY_WEIGHT,
+            frecencies
+                .iter()
+                .map(|(label, frecency)| (label.to_string(), *frecency))
+                .collect(),
+        )
+    }
+
+    fn items(labels: &[&str]) -> Vec<Item> {
+        labels
+            .iter()
+            .map(|label| Item::new(*label, Source::Executable))
+            .collect()
+    }
+
+    fn filter<'a>(matcher: &Matcher, input: &str, items: &'a [Item]) -> Vec<&'a str> {
+        matcher
+            .filter(input, items)
+            .into_iter()
+            .map(|i| items[i].label.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn ranks_prefix_matches_first() {
+        let items = items(&["vscode-helper-xyz", "xcode-select", "code"]);
+
+        assert_eq!(
+            filter(&matcher(&[]), "code", &items),
+            ["code", "xcode-select", "vscode-helper-xyz"]
+        );
+    }
+
+    #[test]
+    fn leaves_out_items_that_dont_match() {
+        let items = items(&["firefox", "thunderbird"]);
+
+        assert_eq!(filter(&matcher(&[]), "fx", &items), ["firefox"]);
+        assert!(filter(&matcher(&[]), "xyz", &items).is_empty());
+    }
+
+    #[test]
+    fn keeps_the_order_without_input() {
+        let items = items(&["b", "c", "a"]);
+
+        assert_eq!(filter(&matcher(&[]), "", &items), ["b", "c", "a"]);
+    }
+
+    #[test]
+    fn matches_keywords_when_the_label_doesnt() {
+        let mut items = items(&["firefox", "chromium"]);
+        items[0].keywords = vec![String::from("browser")];
+
+        assert_eq!(filter(&matcher(&[]), "browser", &items), ["firefox"]);
+    }
+
+    #[test]
+    fn weights_launch_history() {
+        let items = items(&["htop", "top"]);
+
+        assert_eq!(filter(&matcher(&[]), "top", &items), ["top", "htop"]);
+        assert_eq!(
+            filter(&matcher(&[("htop", 10.0)]), "top", &items),
+            ["htop", "top"]
+        );
+    }
+
+    #[test]
+    fn sorts_by_frecency_keeping_the_order_of_the_rest() {
+        let mut items = items(&["a", "b", "c", "d"]);
+        matcher(&[("c", 2.0), ("d", 1.0)]).sort_by_frecency(&mut items);
+
+        let labels: Vec<&str> = items.iter().map(|item| item.label.as_str()).collect();
+        assert_eq!(labels, ["c", "d", "a", "b"]);
+    }
+
+    #[test]
+    fn breaks_ties_by_prefix_exact_match_length_and_name() {
+        assert_eq!(compare_ties("code-oss", "xcode", "code"), Ordering::Less);
+        assert_eq!(compare_ties("code", "code-oss", "code"), Ordering::Less);
+        assert_eq!(compare_ties("ab", "abc", "x"), Ordering::Less);
+        assert_eq!(compare_ties("Abc", "abd", "x"), Ordering::Less);
+        assert_eq!(compare_ties("abc", "ABC", "x"), Ordering::Equal);
+    }
+}
@@ -1,400 +1,778 @@
-use std::time::Duration;
+use std::{
+    collections::HashMap,
+    sync::mpsc::{Receiver, TryRecvError},
+};
 
-use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
 use sdl2::{
     event::Event,
-    keyboard::{Keycode, Mod},
+    mouse::{MouseButton, MouseWheelDirection},
+    pixels::Color,
     rect::Rect,
-    render::{Canvas, WindowCanvas},
-    ttf,
-    video::Window,
-    Sdl, VideoSubsystem,
+    render::{BlendMode, Canvas, WindowCanvas},
+    ttf::{self, FontStyle},
+    video::{Window, WindowPos},
+    Sdl,
 };
 
 use crate::{
-    config::{RunnerMenuSettings, PADDING},
-    utils::{color_from_hex, get_font_path},
+    config::RunnerMenuSettings,
+    error::RunnerError,
+    geometry::{Layout, Position},
+    icons::IconLookup,
+    item::{Item, Selection},
+    keymap::{key_name, Action},
+    utils::{get_font_path, parse_color},
 };
 
+use input::LineEditor;
+use layout::{draw_text, grid_cell_height, row_height, Colors, Fonts, Frame, ItemTextures, View};
+use matcher::Matcher;
+
+mod input;
+mod layout;
+mod matcher;
+
+const LOADING_TEXT: &str = "loading…";
+// in milliseconds
+const LOADING_POLL_INTERVAL: u32 = 30;
+
 pub struct Runner {
     prompt: String,
-    executables: Vec<String>,
+    executables: Vec<Item>,
+    // new items from the item sources, which run in the background while the menu is open and
+    // are done once all senders are dropped
+    items: Receiver<Vec<Item>>,
+    matcher: Matcher,
+    icon_lookup: Option<IconLookup>,
     context: Sdl,
     canvas: Canvas<Window>,
     ttf: ttf::Sdl2TtfContext,
     font_path: String,
-    input: String,
+    input: LineEditor,
     window_size: (u32, u32),
     settings: RunnerMenuSettings,
     target_display_index: Option<i32>,
 }
 
 impl Runner {
-    pub fn new(prompt: String, executables: Vec<String>, settings: RunnerMenuSettings) -> Self {
-        let context = sdl2::init().expect("Error creating SDL context");
-
-        let ttf = ttf::init().expect("Error creating SDL TTF context");
+    pub fn new(
+        prompt: String,
+        items: Receiver<Vec<Item>>,
+        frecencies: HashMap<String, f64>,
+        settings: RunnerMenuSettings,
+    ) -> Result<Self, RunnerError> {
+        let context = sdl2::init().map_err(RunnerError::sdl("Error creating SDL context"))?;
+
+        let ttf = ttf::init().map_err(|e| RunnerError::Sdl {
+            context: "Error creating SDL TTF context",
+            message: e.to_string(),
+        })?;
 
         let font_path: String;
-        let (window_width, window_height): (u32, u32);
-        window_width = 480 + (settings.border_size * 2) as u32;
+        let window_height: u32;
 
         {
             font_path = get_font_path(match settings.font {
                 Some(ref font_p) => font_p.clone(),
                 None => String::from("Monospace"),
-            })
-            .expect("Could not load fonts");
-
-            let font = ttf
-                .load_font(&font_path, settings.font_size)
-                .expect(&format!("Error loading font {}", &font_path));
-
-            window_height = (PADDING
-                + ((font.height() as u16 + settings.line_spacing) * (1 + settings.rows))
-                - settings.line_spacing.div_euclid(2)
-                + PADDING
-                + (settings.border_size * 2) as u16)
-                .into();
+            })?;
+
+            let font = load_font(&ttf, &font_path, settings.font_size)?;
+            let description_font = load_font(&ttf, &font_path, settings.description_font_size)?;
+            let text_heights = (font.height() as u32, description_font.height() as u32);
+
+            let line_spacing = settings.line_spacing as u32;
+
+            // the horizontal layout has the items on the same line as the input
+            let items_height = match settings.layout {
+                Layout::Vertical => {
+                    (row_height(text_heights, &settings) + line_spacing) * settings.rows as u32
+                }
+                Layout::Horizontal => 0,
+                Layout::Grid => grid_cell_height(text_heights, &settings) * settings.rows as u32,
+            };
+
+            window_height =
+                settings.padding as u32 + font.height() as u32 + line_spacing + items_height
+                    - line_spacing.div_euclid(2)
+                    + settings.padding as u32
+                    + settings.border_size as u32 * 2;
         }
 
-        let video = context.video().expect("Error initializing SDL video");
+        let video = context
+            .video()
+            .map_err(RunnerError::sdl("Error initializing SDL video"))?;
+
+        // `0` or `0%` would leave no room between the borders
+        let min_width = settings.border_size as u32 * 2 + 1;
 
         let window = video
-            .window("Practical runner", window_width, window_height)
+            .window(
+                "Practical runner",
+                settings.width.resolve(0).max(min_width),
+                window_height,
+            )
             .borderless()
             .always_on_top()
             .set_shaped()
             .build()
-            .expect("Error creating window");
+            .map_err(|e| RunnerError::Sdl {
+                context: "Error creating window",
+                message: e.to_string(),
+            })?;
+
+        let mut canvas = window.into_canvas().build().map_err(|e| RunnerError::Sdl {
+            context: "Error creating canvas",
+            message: e.to_string(),
+        })?;
 
-        let mut canvas = window.into_canvas().build().expect("Error creating canvas");
+        // draw colors with an alpha channel translucently
+        canvas.set_blend_mode(BlendMode::Blend);
 
         canvas.present();
 
         // If we don't call this before window.display_index() it always returns 0
         let _ = context
             .event_pump()
-            .expect("Error getting SDL event pump")
+            .map_err(RunnerError::sdl("Error getting SDL event pump"))?
             .poll_iter()
             .count();
 
-        let target_display_index: Option<i32>;
-
         let current_display_index = canvas
             .window()
             .display_index()
-            .expect("Error getting window display index");
+            .map_err(RunnerError::sdl("Error getting window display index"))?;
 
-        match settings.display_index {
-            Some(display_index) => {
-                target_display_index = Some(current_display_index);
-                center_on_display(canvas.window_mut(), display_index.into(), &video);
-            }
-            None => {
-                target_display_index = None;
-                center_on_display(canvas.window_mut(), current_display_index.into(), &video);
+        let target_display_index = settings.display_index.map(|_| current_display_index);
+
+        let display_count = video
+            .num_video_displays()
+            .map_err(RunnerError::sdl("Error getting number of displays"))?;
+
+        let mouse_position = global_mouse_position();
+
+        // the window goes on the display with the mouse cursor when it's placed at the mouse
+        let display_index = match (settings.position, settings.display_index) {
+            (_, Some(display_index)) if i32::from(display_index) < display_count => {
+                display_index.into()
             }
-        }
+            (Position::Mouse, _) => (0..display_count)
+                .find(|index| {
+                    video
+                        .display_bounds(*index)
+                        .is_ok_and(|bounds| bounds.contains_point(mouse_position))
+                })
+                .unwrap_or(current_display_index),
+            _ => current_display_index,
+        };
+
+        let bounds = video
+            .display_bounds(display_index)
+            .map_err(RunnerError::sdl("Error getting display bounds"))?;
+
+        let window_width = if settings.position.is_bar() {
+            bounds.width()
+        } else {
+            settings.width.resolve(bounds.width()).max(min_width)
+        };
+
+        let window = canvas.window_mut();
+
+        window
+            .set_size(window_width, window_height)
+            .map_err(|e| RunnerError::Sdl {
+                context: "Error resizing window",
+                message: e.to_string(),
+            })?;
+
+        let (x, y) = settings.position.place(
+            bounds,
+            (window_width, window_height),
+            settings.offset,
+            mouse_position,
+        );
 
-        canvas.window_mut().raise();
+        window.set_position(WindowPos::Positioned(x), WindowPos::Positioned(y));
 
-        let mut cloned_executables = executables.clone();
-        cloned_executables.sort_by(|a, b| a.to_lowercase().cmp(&b.to_lowercase()));
+        canvas.window_mut().raise();
 
-        Self {
+        Ok(Self {
             prompt,
-            executables: cloned_executables,
+            executables: Vec::new(),
+            items,
+            matcher: Matcher::new(
+                settings.word_boundary_bonus,
+                settings.prefix_bonus,
+                settings.history_weight,
+                frecencies,
+            ),
+            icon_lookup: settings
+                .icons
+                .then(|| IconLookup::new(settings.icon_theme.as_deref())),
             context,
             canvas,
-            input: String::from(""),
+            input: LineEditor::default(),
             ttf,
             window_size: (window_width, window_height),
             settings,
             font_path,
             target_display_index,
-        }
+        })
     }
 
-    pub fn run(&mut self) -> Option<String> {
-        let matcher = SkimMatcherV2::default();
-
+    pub fn run(&mut self) -> Result<Option<Selection>, RunnerError> {
         let mut selection_index: u16 = 0;
-        let mut filtered_executables = self.executables.clone();
-
-        let background_color = color_from_hex(&self.settings.background_color).unwrap();
-        let background_color_active =
-            color_from_hex(&self.settings.background_color_active).unwrap();
-        let font_color = color_from_hex(&self.settings.font_color).unwrap();
-        let font_color_active = color_from_hex(&self.settings.font_color_active).unwrap();
+        // indices into the items
+        let mut filtered_executables: Vec<usize> = (0..self.executables.len()).collect();
+        let mut selection: Option<Selection> = None;
+
+        let background_color = parse_color(&self.settings.background_color, "background-color")?;
+        let font_color = parse_color(&self.settings.font_color, "font-color")?;
+        let font_color_active = parse_color(&self.settings.font_color_active, "font-color-active")?;
+
+        let colors = Colors {
+            font: font_color,
+            font_active: font_color_active,
+            highlight: parse_color(&self.settings.highlight_color, "highlight-color")?,
+            highlight_active: parse_color(
+                &self.settings.highlight_color_active,
+                "highlight-color-active",
+            )?,
+            background_active: parse_color(
+                &self.settings.background_color_active,
+                "background-color-active",
+            )?,
+            description: match self.settings.description_color {
+                Some(ref color) => parse_color(color, "description-color")?,
+                None => dimmed(font_color),
+            },
+            description_active: match self.settings.description_color_active {
+                Some(ref color) => parse_color(color, "description-color-active")?,
+                None => dimmed(font_color_active),
+            },
+            border: parse_color(&self.settings.border_color, "border-color")?,
+        };
+
+        let mut highlight_font = load_font(&self.ttf, &self.font_path, self.settings.font_size)?;
+
+        let mut highlight_style = FontStyle::NORMAL;
+        if self.settings.highlight_bold {
+            highlight_style |= FontStyle::BOLD;
+        }
+        if self.settings.highlight_underline {
+            highlight_style |= FontStyle::UNDERLINE;
+        }
+        highlight_font.set_style(highlight_style);
+
+        let fonts = Fonts {
+            label: load_font(&self.ttf, &self.font_path, self.settings.font_size)?,
+            highlight: highlight_font,
+            description: load_font(
+                &self.ttf,
+                &self.font_path,
+                self.settings.description_font_size,
+            )?,
+            initial: load_font(
+                &self.ttf,
+                &self.font_path,
+                (self.settings.icon_size / 2).max(1),
+            )?,
+        };
+        let font = &fonts.label;
 
-        let border_color = color_from_hex(&self.settings.border_color).unwrap();
+        let creator = self.canvas.texture_creator();
+        let mut textures = ItemTextures::new(&creator);
 
-        let font = self
-            .ttf
-            .load_font(&self.font_path, self.settings.font_size)
-            .expect(&format!("Error loading font {}", self.font_path));
+        let mut event_pump = self
+            .context
+            .event_pump()
+            .map_err(RunnerError::sdl("Error getting SDL event pump"))?;
 
-        let creator = self.canvas.texture_creator();
+        let mut view = View::new(self.settings.rows);
+        // whether the visible items should follow the selection, which they don't when the
+        // selection is moved by hovering so the rows don't move away under the cursor
+        let mut follow_selection = true;
+        // SDL reports a motion when the window opens under the pointer, only hovering after
+        // the pointer actually moved selects an item
+        let mut pointer_moved = false;
 
-        let mut event_pump = self.context.event_pump().unwrap();
+        let mut redraw = true;
+        let mut loading = true;
 
         'run: loop {
-            if !self.canvas.window().has_input_focus() {
-                self.input.clear();
+            // SDL only reports clicks inside the window, clicking elsewhere closes the menu by
+            // taking away the focus
+            if !self.settings.keep_open && !self.canvas.window().has_input_focus() {
                 break 'run;
             }
 
-            self.canvas.set_draw_color(background_color);
-            self.canvas.clear();
-            self.canvas.set_draw_color(border_color);
-            draw_borders(
-                self.settings.border_size,
-                self.window_size,
-                &mut self.canvas,
-            );
+            let mut received = false;
+            // the selected item, taken before new items are added since the indices change
+            let mut selected: Option<Item> = None;
+            while loading {
+                match self.items.try_recv() {
+                    Ok(items) => {
+                        if !received {
+                            selected = filtered_executables
+                                .get(selection_index as usize)
+                                .map(|&i| self.executables[i].clone());
+                        }
+                        self.executables.extend(items);
+                        received = true;
+                    }
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        loading = false;
+                        redraw = true;
+                    }
+                }
+            }
+
+            if received {
+                if self.settings.sort_items {
+                    self.executables
+                        .sort_by_key(|item| item.label.to_lowercase());
+                    self.matcher.sort_by_frecency(&mut self.executables);
+                }
 
-            for event in event_pump.poll_iter() {
-                match event {
-                    Event::Quit { .. }
-                    | Event::KeyDown {
-                        keycode: Some(Keycode::Escape),
-                        ..
+                filter_executables(
+                    self.input.as_str(),
+                    &self.executables,
+                    &mut filtered_executables,
+                    &self.matcher,
+                );
+
+                // new items can end up before the selection, which should stay selected
+                selection_index = selected
+                    .and_then(|selected| {
+                        filtered_executables
+                            .iter()
+                            .position(|&i| self.executables[i] == selected)
+                    })
+                    .unwrap_or(0) as u16;
+
+                redraw = true;
+            }
+
+            if redraw {
+                // the window itself is opaque, so translucent colors are blended over black
+                self.canvas.set_draw_color(Color::BLACK);
+                self.canvas.clear();
+                self.canvas.set_draw_color(background_color);
+                let _ = self.canvas.fill_rect(None);
+                self.canvas.set_draw_color(colors.border);
+                draw_borders(
+                    self.settings.border_size,
+                    self.window_size,
+                    &mut self.canvas,
+                );
+
+                let mut cursor_offset_x = self.settings.padding + self.settings.border_size as u16;
+                // without padding and border the input starts at the top of the window
+                let input_position_y: u16 = (self.settings.padding
+                    + self.settings.border_size as u16
+                    + self.settings.line_spacing.div_ceil(4))
+                .saturating_sub(1);
+
+                let input_surface = if !self.input.is_empty() || !self.prompt.is_empty() {
+                    font.render(&format!("{}{}", &self.prompt, self.input.as_str()))
+                        .blended(font_color)
+                        .ok()
+                } else {
+                    None
+                };
+
+                // for the horizontal layout, which puts the items after the input
+                let mut input_width = 0;
+
+                if let Some(surface) = input_surface {
+                    input_width = surface.width();
+
+                    let rect = Rect::new(
+                        (self.settings.padding + self.settings.border_size as u16).into(),
+                        input_position_y.into(),
+                        surface.width(),
+                        surface.height(),
+                    );
+
+                    let (cursor_text_width, _) = font
+                        .size_of(&format!(
+                            "{}{}",
+                            &self.prompt,
+                            &self.input.as_str()[..self.input.cursor()]
+                        ))
+                        .unwrap_or((0, 0));
+                    cursor_offset_x += cursor_text_width as u16;
+
+                    if let Ok(texture) = creator.create_texture_from_surface(surface) {
+                        let _ = self.canvas.copy(&texture, None, Some(rect));
                     }
-                    | Event::KeyDown {
-                        keycode: Some(Keycode::C),
-                        keymod: Mod::LCTRLMOD,
+                }
+
+                // the items that are still being looked for are shown as they arrive
+                let mut loading_width = 0;
+                if loading {
+                    let (width, _) = font.size_of(LOADING_TEXT).unwrap_or((0, 0));
+                    loading_width = width as i32 + self.settings.padding as i32;
+
+                    draw_text(
+                        &mut self.canvas,
+                        &creator,
+                        font,
+                        LOADING_TEXT,
+                        dimmed(font_color),
+                        (
+                            self.window_size.0 as i32
+                                - (self.settings.border_size as u16 + self.settings.padding) as i32
+                                - width as i32,
+                            input_position_y.into(),
+                        ),
+                    );
+                }
+
+                if self.canvas.window().has_input_focus() {
+                    let cursor_rect = Rect::new(
+                        cursor_offset_x.into(),
+                        input_position_y.into(),
+                        3,
+                        font.height() as u32,
+                    );
+
+                    self.canvas.set_draw_color(colors.background_active);
+                    let _ = self.canvas.fill_rect(cursor_rect);
+                }
+
+                Frame {
+                    canvas: &mut self.canvas,
+                    textures: &mut textures,
+                    icon_lookup: &mut self.icon_lookup,
+                    fonts: &fonts,
+                    colors: &colors,
+                    settings: &self.settings,
+                    window_size: self.window_size,
+                    items: &self.executables,
+                    filtered: &filtered_executables,
+                    selection_index,
+                    follow_selection,
+                    matcher: &self.matcher,
+                    query: self.input.as_str(),
+                    input_y: input_position_y.into(),
+                    input_width,
+                    loading_width,
+                }
+                .draw(&mut view);
+
+                self.canvas.present();
+
+                textures.evict_unused();
+
+                redraw = false;
+            }
+
+            // block until something happens instead of redrawing continuously, while items are
+            // still coming in only briefly to check for them
+            let first_event = if loading {
+                event_pump.wait_event_timeout(LOADING_POLL_INTERVAL)
+            } else {
+                Some(event_pump.wait_event())
+            };
+
+            for event in first_event.into_iter().chain(event_pump.poll_iter()) {
+                match event {
+                    Event::Quit { .. } => break 'run,
+                    Event::KeyDown {
+                        keycode: Some(keycode),
+                        keymod,
                         ..
                     } => {
-                        self.input = String::from("");
-                        break 'run;
-                    }
-                    Event::KeyDown { keycode, .. } => {
-                        if let Some(key) = keycode {
-                            match key {
-                                Keycode::Backspace => {
-                                    if !self.input.is_empty() {
-                                        self.input.pop();
-
-                                        filter_executables(
-                                            &self.input,
-                                            &self.executables,
-                                            &mut filtered_executables,
-                                            &matcher,
-                                        );
-                                        selection_index = 0;
-                                    }
+                        let Some(action) = self.settings.keymap.action(keycode, keymod) else {
+                            continue;
+                        };
+
+                        follow_selection = true;
+
+                        let executables_len = filtered_executables.len() as u16;
+
+                        match action {
+                            Action::Cancel => break 'run,
+                            // the input is used as is when nothing matches
+                            Action::Accept | Action::AcceptInput => {
+                                let item = match action {
+                                    Action::Accept => filtered_executables
+                                        .get(selection_index as usize)
+                                        .map(|&i| self.executables[i].clone()),
+                                    _ => None,
+                                };
+
+                                selection = accepted(
+                                    item,
+                                    &self.input,
+                                    action.name(),
+                                    Some(key_name(keycode, keymod)),
+                                );
+                                break 'run;
+                            }
+                            // the grid moves a row at a time, to the last item if the next row
+                            // isn't full
+                            Action::Next if self.settings.layout == Layout::Grid => {
+                                let columns = self.settings.columns;
+                                let last = executables_len.saturating_sub(1);
+
+                                if selection_index / columns < last / columns {
+                                    selection_index = (selection_index + columns).min(last);
                                 }
-                                Keycode::Return => {
-                                    let executables_len = filtered_executables.len();
-                                    if executables_len > 0 {
-                                        self.input = filtered_executables
-                                            [executables_len.min(selection_index.into()) as usize]
-                                            .clone();
-                                    }
-                                    break 'run;
+                            }
+                            Action::Prev if self.settings.layout == Layout::Grid => {
+                                if selection_index >= self.settings.columns {
+                                    selection_index -= self.settings.columns;
                                 }
-                                Keycode::Down => {
-                                    if selection_index < (filtered_executables.len() - 1) as u16 {
-                                        selection_index += 1;
-                                    }
+                            }
+                            Action::Next => {
+                                if selection_index + 1 < executables_len {
+                                    selection_index += 1;
                                 }
-                                Keycode::Up => {
-                                    if selection_index > 0 {
-                                        selection_index -= 1;
-                                    }
+                            }
+                            Action::Prev => {
+                                selection_index = selection_index.saturating_sub(1);
+                            }
+                            Action::PageDown => {
+                                selection_index = (selection_index + view.page_size)
+                                    .min(executables_len.saturating_sub(1));
+                            }
+                            Action::PageUp => {
+                                selection_index = selection_index.saturating_sub(view.page_size);
+                            }
+                            // like dmenu, left and right move the selection once the cursor
+                            // can't move any further, in the grid they always do
+                            Action::CursorLeft
+                                if self.settings.layout == Layout::Grid
+                                    || self.settings.layout == Layout::Horizontal
+                                        && self.input.cursor() == 0 =>
+                            {
+                                selection_index = selection_index.saturating_sub(1);
+                            }
+                            Action::CursorRight
+                                if self.settings.layout == Layout::Grid
+                                    || self.settings.layout == Layout::Horizontal
+                                        && self.input.cursor() == self.input.as_str().len() =>
+                            {
+                                if selection_index + 1 < executables_len {
+                                    selection_index += 1;
+                                }
+                            }
+                            Action::First => selection_index = 0,
+                            Action::Last => selection_index = executables_len.saturating_sub(1),
+                            Action::Complete => {
+                                if let Some(&i) = filtered_executables.get(selection_index as usize)
+                                {
+                                    self.input.set(&self.executables[i].label);
+
+                                    filter_executables(
+                                        self.input.as_str(),
+                                        &self.executables,
+                                        &mut filtered_executables,
+                                        &self.matcher,
+                                    );
+                                    selection_index = 0;
                                 }
-                                Keycode::Tab => {
-                                    if filtered_executables.len() > 0 {
-                                        self.input =
-                                            filtered_executables[selection_index as usize].clone();
-
-                                        filter_executables(
-                                            &self.input,
-                                            &self.executables,
-                                            &mut filtered_executables,
-                                            &matcher,
-                                        );
-                                        selection_index = 0;
-                                    }
+                            }
+                            _ => {
+                                if edit_input(&mut self.input, action) {
+                                    filter_executables(
+                                        self.input.as_str(),
+                                        &self.executables,
+                                        &mut filtered_executables,
+                                        &self.matcher,
+                                    );
+                                    selection_index = 0;
                                 }
-                                _ => (),
                             }
                         }
                     }
+                    Event::MouseMotion {
+                        xrel: 0, yrel: 0, ..
+                    } => continue,
+                    Event::MouseMotion { .. } if !pointer_moved => {
+                        pointer_moved = true;
+                        continue;
+                    }
+                    Event::MouseMotion { x, y, .. } => match view.item_at(x, y) {
+                        Some(hovered_index)
+                            if hovered_index != selection_index
+                                && (hovered_index as usize) < filtered_executables.len() =>
+                        {
+                            selection_index = hovered_index;
+                            follow_selection = false;
+                        }
+                        _ => continue,
+                    },
+                    Event::MouseButtonDown {
+                        mouse_btn: MouseButton::Left,
+                        x,
+                        y,
+                        ..
+                    } => {
+                        if let Some(&i) = view
+                            .item_at(x, y)
+                            .and_then(|index| filtered_executables.get(index as usize))
+                        {
+                            selection = accepted(
+                                Some(self.executables[i].clone()),
+                                &self.input,
+                                "click",
+                                None,
+                            );
+                            break 'run;
+                        }
+                        continue;
+                    }
+                    Event::MouseWheel { y, direction, .. } => {
+                        let scroll_down = match direction {
+                            MouseWheelDirection::Flipped => y > 0,
+                            _ => y < 0,
+                        };
+                        let executables_len = filtered_executables.len() as u16;
+
+                        if scroll_down && selection_index + 1 < executables_len {
+                            selection_index += 1;
+                        } else if !scroll_down && y != 0 && selection_index > 0 {
+                            selection_index -= 1;
+                        } else {
+                            continue;
+                        }
+                        follow_selection = true;
+                    }
                     Event::TextInput { text, .. } => {
-                        self.input += &text;
+                        follow_selection = true;
+
+                        self.input.insert(&text);
 
                         filter_executables(
-                            &self.input,
+                            self.input.as_str(),
                             &self.executables,
                             &mut filtered_executables,
-                            &matcher,
+                            &self.matcher,
                         );
 
                         selection_index = 0;
                     }
-                    _ => {}
+                    Event::Window { .. } => (),
+                    _ => continue,
                 }
-            }
-
-            let mut cursor_offset_x = PADDING + self.settings.border_size as u16;
-            let input_position_y: u16 = (PADDING + self.settings.border_size as u16 - 1
-                + self.settings.line_spacing.div_ceil(4))
-            .into();
-
-            if !self.input.is_empty() || !self.prompt.is_empty() {
-                let surface = font
-                    .render(&format!("{}{}", &self.prompt, &self.input))
-                    .blended(font_color)
-                    .expect("Error rendering text");
-
-                let rect = Rect::new(
-                    (PADDING + self.settings.border_size as u16).into(),
-                    input_position_y.into(),
-                    surface.width(),
-                    surface.height(),
-                );
 
-                cursor_offset_x += surface.width() as u16;
-
-                let texture = creator
-                    .create_texture_from_surface(surface)
-                    .expect("Error creating texture");
-
-                let _ = self.canvas.copy(&texture, None, Some(rect));
+                redraw = true;
             }
+        }
 
-            if self.canvas.window().has_input_focus() {
-                let cursor_rect = Rect::new(
-                    cursor_offset_x.into(),
-                    input_position_y.into(),
-                    3,
-                    font.height() as u32,
+        if selection.is_none() {
+            Ok(None)
+        } else {
+            if let Some(target_display_index) = self.target_display_index {
+                let window = self.canvas.window_mut();
+                let target_display_bounds = self
+                    .context
+                    .video()
+                    .map_err(RunnerError::sdl("Error getting SDL video"))?
+                    .display_bounds(target_display_index)
+                    .map_err(RunnerError::sdl("Error getting target display bounds"))?;
+
+                window.set_position(
+                    WindowPos::Positioned(target_display_bounds.x()),
+                    WindowPos::Positioned(target_display_bounds.y()),
                 );
 
-                self.canvas.set_draw_color(background_color_active);
-                let _ = self.canvas.fill_rect(cursor_rect);
+                window.raise();
+                window.hide();
             }
 
-            // try to keep the selection centered
-            let executables_len: u16 = filtered_executables.len() as u16;
-            let start: u16 = selection_index
-                .saturating_sub(self.settings.rows.div_euclid(2))
-                .min(executables_len.saturating_sub(self.settings.rows));
-            let end: u16 = (start + self.settings.rows).min(executables_len);
-            // ---
-
-            let half_line_spacing = self.settings.line_spacing.div_euclid(2);
-
-            let mut display_count: u16 = 0;
-
-            for i in start..end {
-                let offset = self.settings.border_size as u16
-                    + PADDING * 2
-                    + (font.height() as u16 + self.settings.line_spacing) * (display_count + 1);
-
-                let surface = font
-                    .render(&filtered_executables[i as usize])
-                    .blended(if i != selection_index {
-                        font_color
-                    } else {
-                        font_color_active
-                    })
-                    .expect("Error rendering text");
-
-                let rect = Rect::new(
-                    (self.settings.border_size as u16 + PADDING).into(),
-                    offset.into(),
-                    surface.width(),
-                    surface.height(),
-                );
-
-                let background_rect = Rect::new(
-                    self.settings.border_size.into(),
-                    (offset - half_line_spacing).into(),
-                    self.window_size.0 - (self.settings.border_size as u32) * 2,
-                    (surface.height() + self.settings.line_spacing as u32).into(),
-                );
-
-                self.canvas.set_draw_color(if i != selection_index {
-                    background_color
-                } else {
-                    background_color_active
-                });
-
-                let _ = self.canvas.fill_rect(background_rect);
-
-                let texture = creator
-                    .create_texture_from_surface(surface)
-                    .expect("Error creating texture");
+            Ok(selection)
+        }
+    }
+}
 
-                let _ = self.canvas.copy(&texture, None, Some(rect));
+fn filter_executables(
+    input: &str,
+    executables: &[Item],
+    filtered_executables: &mut Vec<usize>,
+    matcher: &Matcher,
+) {
+    *filtered_executables = matcher.filter(input, executables);
+}
 
-                display_count += 1;
-            }
+// Without an item the typed text is accepted, unless there is none
+fn accepted(
+    item: Option<Item>,
+    input: &LineEditor,
+    action: &'static str,
+    key: Option<String>,
+) -> Option<Selection> {
+    if item.is_none() && input.is_empty() {
+        return None;
+    }
 
-            self.canvas.present();
+    Some(Selection {
+        item,
+        query: input.as_str().to_string(),
+        action,
+        key,
+    })
+}
 
-            std::thread::sleep(Duration::from_millis(8));
+// Applies a line editing action to the input and returns whether the text changed
+fn edit_input(input: &mut LineEditor, action: Action) -> bool {
+    match action {
+        Action::DeleteCharBackward => input.delete_backward(),
+        Action::DeleteChar => input.delete_forward(),
+        Action::DeleteWord => input.delete_word_backward(),
+        Action::DeleteWordAlphanumeric => input.delete_alphanumeric_word_backward(),
+        Action::KillToStart => input.kill_to_start(),
+        Action::KillToEnd => input.kill_to_end(),
+        Action::Yank => input.yank(),
+        Action::CursorLeft => {
+            input.move_left();
+            false
         }
-
-        if self.input.is_empty() {
-            None
-        } else {
-            match self.target_display_index {
-                Some(target_display_index) => {
-                    let window = self.canvas.window_mut();
-                    let target_display_bounds = self
-                        .context
-                        .video()
-                        .expect("Error getting SDL video")
-                        .display_bounds(target_display_index)
-                        .expect("Error getting target display bounds");
-
-                    window.set_position(
-                        sdl2::video::WindowPos::Positioned(target_display_bounds.x()),
-                        sdl2::video::WindowPos::Positioned(target_display_bounds.y()),
-                    );
-
-                    window.raise();
-                    window.hide();
-                }
-                None => (),
-            }
-
-            Some(self.input.clone())
+        Action::CursorRight => {
+            input.move_right();
+            false
+        }
+        Action::WordLeft => {
+            input.move_word_left();
+            false
         }
+        Action::WordRight => {
+            input.move_word_right();
+            false
+        }
+        Action::LineStart => {
+            input.move_home();
+            false
+        }
+        Action::LineEnd => {
+            input.move_end();
+            false
+        }
+        _ => false,
     }
 }
 
-fn filter_executables(
-    input: &String,
-    executables: &Vec<String>,
-    filtered_executables: &mut Vec<String>,
-    matcher: &SkimMatcherV2,
-) {
-    *filtered_executables = executables
-        .iter()
-        .filter(|e| matcher.fuzzy_indices(*e, input).is_some())
-        .map(|e| e.to_string())
-        .collect();
-
-    filtered_executables.sort_by(|a, b| b.starts_with(input).cmp(&a.starts_with(input)));
+// Half as opaque, for secondary text
+fn dimmed(color: Color) -> Color {
+    Color::RGBA(color.r, color.g, color.b, color.a / 2)
 }
 
 fn draw_borders(border_size: u8, window_size: (u32, u32), canvas: &mut WindowCanvas) {
     if border_size > 0 {
         let _ = canvas.fill_rect(Rect::new(0, 0, window_size.0, border_size.into()));
         let _ = canvas.fill_rect(Rect::new(
-            (window_size.0 - border_size as u32) as i32,
+            window_size.0.saturating_sub(border_size as u32) as i32,
             0,
             border_size.into(),
             window_size.1,
         ));
         let _ = canvas.fill_rect(Rect::new(
             0,
-            (window_size.1 - border_size as u32) as i32,
+            window_size.1.saturating_sub(border_size as u32) as i32,
             window_size.0,
             border_size.into(),
         ));
@@ -402,27 +780,25 @@ fn draw_borders(border_size: u8, window_size: (u32, u32), canvas: &mut WindowCan
     }
 }
 
-fn center_on_display(window: &mut Window, display_index: i32, video: &VideoSubsystem) {
-    if (display_index as i32).lt(&video
-        .num_video_displays()
-        .expect("Error getting number of displays"))
-    {
-        let bounds = video.display_bounds(display_index.into()).expect(&format!(
-            "Error getting bounds for display {}",
-            display_index
-        ));
+fn load_font<'ttf>(
+    ttf: &'ttf ttf::Sdl2TtfContext,
+    path: &str,
+    size: u16,
+) -> Result<ttf::Font<'ttf, 'static>, RunnerError> {
+    ttf.load_font(path, size)
+        .map_err(|message| RunnerError::FontLoad {
+            path: path.to_string(),
+            message,
+        })
+}
 
-        let window_size = window.size();
+// SDL only reports the mouse position relative to its own windows without this
+fn global_mouse_position() -> (i32, i32) {
+    let (mut x, mut y) = (0, 0);
 
-        window.set_position(
-            sdl2::video::WindowPos::Positioned(
-                bounds.x() + bounds.width().div_euclid(2) as i32
-                    - window_size.0.div_euclid(2) as i32,
-            ),
-            sdl2::video::WindowPos::Positioned(
-                bounds.y() + bounds.height().div_euclid(2) as i32
-                    - window_size.1.div_euclid(2) as i32,
-            ),
-        );
+    unsafe {
+        sdl2::sys::SDL_GetGlobalMouseState(&mut x, &mut y);
     }
+
+    (x, y)
 }
@@ -0,0 +1,182 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use serde::Deserialize;
+
+use super::Theme;
+use crate::utils::{color_from_str, xdg_dir};
+
+// The color slots of the imported palettes, in the order of the theme's colors: font, active
+// font, highlight, active highlight, background, active background and border. pywal and
+// Xresources both describe a terminal's 16 color palette.
+const TERMINAL_SLOTS: [&str; 7] = [
+    "foreground",
+    "background",
+    "color3",
+    "color11",
+    "background",
+    "color4",
+    "color8",
+];
+const BASE16_SLOTS: [&str; 7] = [
+    "base05", "base00", "base0A", "base07", "base00", "base0D", "base03",
+];
+
+#[derive(Deserialize)]
+struct PywalColors {
+    #[serde(default)]
+    special: HashMap<String, String>,
+    #[serde(default)]
+    colors: HashMap<String, String>,
+}
+
+// Imports the colors from `pywal`, `xresources` or a file: `.json` files are read like pywal's
+// colors.json, `.yaml`/`.yml` files as base16 schemes and anything else as Xresources
+pub fn import_colors(source: &str) -> Result<Theme, String> {
+    match source {
+        "pywal" | "wal" => {
+            let path = get_pywal_path().ok_or("could not find pywal's cache directory")?;
+            import_pywal(&path)
+        }
+        "xresources" | "xrdb" => import_xresources(),
+        path => {
+            let path = expand_home(path);
+
+            match path.extension().and_then(|extension| extension.to_str()) {
+                Some("json") => import_pywal(&path),
+                Some("yaml" | "yml") => import_base16(&path),
+                _ => Ok(theme_from_palette(
+                    &parse_xresources(&read(&path)?),
+                    TERMINAL_SLOTS,
+                )),
+            }
+        }
+    }
+}
+
+fn get_pywal_path() -> Option<PathBuf> {
+    Some(
+        xdg_dir("XDG_CACHE_HOME", ".cache")?
+            .join("wal")
+            .join("colors.json"),
+    )
+}
+
+fn import_pywal(path: &Path) -> Result<Theme, String> {
+    let pywal: PywalColors = serde_json::from_str(&read(path)?)
+        .map_err(|e| format!("Invalid pywal colors {}: {}", path.display(), e))?;
+
+    let palette: HashMap<String, String> = pywal.special.into_iter().chain(pywal.colors).collect();
+
+    Ok(theme_from_palette(&palette, TERMINAL_SLOTS))
+}
+
+// Base16 schemes are flat `baseXX: "rrggbb"` mappings, newer ones nest them under `palette:`,
+// so the scheme is read line by line instead of pulling in a YAML parser
+fn import_base16(path: &Path) -> Result<Theme, String> {
+    let mut palette: HashMap<String, String> = HashMap::new();
+
+    for line in read(path)?.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+
+        let key = key.trim();
+        if !key.starts_with("base") {
+            continue;
+        }
+
+        // drop trailing comments and quotes, the `#` is optional in base16 schemes
+        let value = value.split(" #").next().unwrap_or_default().trim();
+        let value = value.trim_matches(|c| c == '"' || c == '\'');
+        let value = value.strip_prefix('#').unwrap_or(value);
+
+        palette.insert(key.to_string(), format!("#{}", value));
+    }
+
+    if palette.is_empty() {
+        return Err(format!("No base16 colors found in {}", path.display()));
+    }
+
+    Ok(theme_from_palette(&palette, BASE16_SLOTS))
+}
+
+// Prefers the loaded resources from `xrdb -query`, which has includes and defines resolved,
+// and falls back to reading ~/.Xresources
+fn import_xresources() -> Result<Theme, String> {
+    let resources = match Command::new("xrdb").arg("-query").output() {
+        Ok(output) if output.status.success() && !output.stdout.is_empty() => {
+            String::from_utf8_lossy(&output.stdout).into_owned()
+        }
+        _ => {
+            let path = home::home_dir()
+                .ok_or("could not find the home directory")?
+                .join(".Xresources");
+            read(&path)?
+        }
+    };
+
+    Ok(theme_from_palette(
+        &parse_xresources(&resources),
+        TERMINAL_SLOTS,
+    ))
+}
+
+// Only the last component of resource names matters, so `*.background`, `*background` and
+// `URxvt.background` all set `background`. Later entries win like they do in xrdb.
+fn parse_xresources(resources: &str) -> HashMap<String, String> {
+    let mut palette: HashMap<String, String> = HashMap::new();
+
+    for line in resources.lines() {
+        let line = line.trim();
+        if line.starts_with('!') || line.starts_with('#') {
+            continue;
+        }
+
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+
+        let key = name.rsplit(['.', '*']).next().unwrap_or_default().trim();
+
+        palette.insert(key.to_string(), value.trim().to_string());
+    }
+
+    palette
+}
+
+fn theme_from_palette(palette: &HashMap<String, String>, slots: [&str; 7]) -> Theme {
+    // colors that are missing or that we can't parse are left to the theme or the defaults
+    let color = |slot: &str| {
+        palette
+            .get(slot)
+            .filter(|value| color_from_str(value).is_some())
+            .cloned()
+    };
+
+    Theme {
+        font_color: color(slots[0]),
+        font_color_active: color(slots[1]),
+        highlight_color: color(slots[2]),
+        highlight_color_active: color(slots[3]),
+        background_color: color(slots[4]),
+        background_color_active: color(slots[5]),
+        border_color: color(slots[6]),
+        ..Default::default()
+    }
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), home::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+fn read(path: &Path) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|e| format!("Could not read {}: {}", path.display(), e))
+}
@@ -0,0 +1,146 @@
+use std::{fs, io, path::PathBuf};
+
+use serde::Deserialize;
+
+use crate::{
+    arguments::{deserialize_color, Arguments},
+    config_file::get_config_dir,
+};
+
+pub use import::import_colors;
+
+mod import;
+
+// Themes shipped in the binary, the files in `themes/` double as examples for custom themes
+pub const THEMES: &[(&str, &str)] = &[
+    (
+        "catppuccin-latte",
+        include_str!("../../themes/catppuccin-latte.toml"),
+    ),
+    (
+        "catppuccin-frappe",
+        include_str!("../../themes/catppuccin-frappe.toml"),
+    ),
+    (
+        "catppuccin-macchiato",
+        include_str!("../../themes/catppuccin-macchiato.toml"),
+    ),
+    (
+        "catppuccin-mocha",
+        include_str!("../../themes/catppuccin-mocha.toml"),
+    ),
+    ("gruvbox", include_str!("../../themes/gruvbox.toml")),
+    ("nord", include_str!("../../themes/nord.toml")),
+    (
+        "solarized-dark",
+        include_str!("../../themes/solarized-dark.toml"),
+    ),
+    (
+        "solarized-light",
+        include_str!("../../themes/solarized-light.toml"),
+    ),
+    ("dmenu", include_str!("../../themes/dmenu.toml")),
+    (
+        "high-contrast",
+        include_str!("../../themes/high-contrast.toml"),
+    ),
+];
+
+// Colors missing from a theme fall back to the defaults in `config.rs`
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Theme {
+    #[serde(deserialize_with = "deserialize_color")]
+    pub font_color: Option<String>,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub font_color_active: Option<String>,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub highlight_color: Option<String>,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub highlight_color_active: Option<String>,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub background_color: Option<String>,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub background_color_active: Option<String>,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub border_color: Option<String>,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub description_color: Option<String>,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub description_color_active: Option<String>,
+}
+
+impl From<Theme> for Arguments {
+    fn from(theme: Theme) -> Self {
+        Self {
+            font_color: theme.font_color,
+            font_color_active: theme.font_color_active,
+            highlight_color: theme.highlight_color,
+            highlight_color_active: theme.highlight_color_active,
+            background_color: theme.background_color,
+            background_color_active: theme.background_color_active,
+            border_color: theme.border_color,
+            description_color: theme.description_color,
+            description_color_active: theme.description_color_active,
+            ..Default::default()
+        }
+    }
+}
+
+pub fn get_themes_dir() -> Option<PathBuf> {
+    Some(get_config_dir()?.join("themes"))
+}
+
+// Loads a theme by name. Theme files in the themes directory take precedence over the built-in
+// themes, so a built-in theme can be tweaked by copying it there.
+pub fn load_theme(name: &str) -> Result<Theme, String> {
+    if let Some(path) = get_themes_dir().map(|dir| dir.join(format!("{}.toml", name))) {
+        match fs::read_to_string(&path) {
+            Ok(content) => {
+                return toml::from_str(&content)
+                    .map_err(|e| format!("Invalid theme file {}: {}", path.display(), e))
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => (),
+            Err(e) => return Err(format!("Could not read {}: {}", path.display(), e)),
+        }
+    }
+
+    match THEMES.iter().find(|(theme, _)| *theme == name) {
+        Some((_, content)) => {
+            toml::from_str(content).map_err(|e| format!("Invalid built-in theme `{}`: {}", name, e))
+        }
+        None => Err(format!(
+            "unknown theme `{}`, run with --list-themes to see all themes",
+            name
+        )),
+    }
+}
+
+// The names of all themes with where they come from, for --list-themes
+pub fn list_themes() -> Vec<(String, String)> {
+    let mut themes: Vec<(String, String)> = Vec::new();
+
+    if let Some(Ok(entries)) = get_themes_dir().map(fs::read_dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+
+            if path
+                .extension()
+                .is_some_and(|extension| extension == "toml")
+            {
+                if let Some(name) = path.file_stem().and_then(|name| name.to_str()) {
+                    themes.push((name.to_string(), path.display().to_string()));
+                }
+            }
+        }
+    }
+
+    for (name, _) in THEMES {
+        if !themes.iter().any(|(theme, _)| theme == name) {
+            themes.push((name.to_string(), String::from("built-in")));
+        }
+    }
+
+    themes.sort();
+    themes
+}
@@ -1,34 +1,281 @@
+use std::{env, fs::Metadata, os::unix::fs::PermissionsExt, path::PathBuf};
+
 use fontconfig::Fontconfig;
 use sdl2::pixels::Color;
 
-#[derive(Debug, Clone)]
-pub struct ColorParseError;
+use crate::error::RunnerError;
+
+const NAMED_COLORS: &[(&str, Color)] = &[
+    ("transparent", Color::RGBA(0, 0, 0, 0)),
+    ("black", Color::RGB(0, 0, 0)),
+    ("silver", Color::RGB(192, 192, 192)),
+    ("gray", Color::RGB(128, 128, 128)),
+    ("grey", Color::RGB(128, 128, 128)),
+    ("white", Color::RGB(255, 255, 255)),
+    ("maroon", Color::RGB(128, 0, 0)),
+    ("red", Color::RGB(255, 0, 0)),
+    ("purple", Color::RGB(128, 0, 128)),
+    ("fuchsia", Color::RGB(255, 0, 255)),
+    ("magenta", Color::RGB(255, 0, 255)),
+    ("green", Color::RGB(0, 128, 0)),
+    ("lime", Color::RGB(0, 255, 0)),
+    ("olive", Color::RGB(128, 128, 0)),
+    ("yellow", Color::RGB(255, 255, 0)),
+    ("navy", Color::RGB(0, 0, 128)),
+    ("blue", Color::RGB(0, 0, 255)),
+    ("teal", Color::RGB(0, 128, 128)),
+    ("aqua", Color::RGB(0, 255, 255)),
+    ("cyan", Color::RGB(0, 255, 255)),
+    ("orange", Color::RGB(255, 165, 0)),
+    ("pink", Color::RGB(255, 192, 203)),
+    ("brown", Color::RGB(165, 42, 42)),
+];
+
+// Parses `#rgb`, `#rgba`, `#rrggbb`, `#rrggbbaa`, `rgb()`/`rgba()`, `hsl()`/`hsla()` and the
+// basic CSS color names
+pub fn color_from_str(value: &str) -> Option<Color> {
+    let value = value.trim();
+
+    if let Some(hex) = value.strip_prefix('#') {
+        return color_from_hex(hex);
+    }
+
+    let lowercase = value.to_ascii_lowercase();
+
+    if let Some((function, arguments)) = lowercase
+        .strip_suffix(')')
+        .and_then(|function| function.split_once('('))
+    {
+        return color_from_function(function.trim(), arguments);
+    }
+
+    NAMED_COLORS
+        .iter()
+        .find(|(name, _)| *name == lowercase)
+        .map(|(_, color)| *color)
+}
+
+fn color_from_hex(hex: &str) -> Option<Color> {
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    // every digit is a byte from here on, so slicing can't split a character
+    let digits: Vec<u8> = match hex.len() {
+        3 | 4 => hex
+            .chars()
+            .map(|c| c.to_digit(16).map(|d| d as u8 * 0x11))
+            .collect::<Option<_>>()?,
+        6 | 8 => (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+            .collect::<Option<_>>()?,
+        _ => return None,
+    };
+
+    Some(Color::RGBA(
+        digits[0],
+        digits[1],
+        digits[2],
+        digits.get(3).copied().unwrap_or(255),
+    ))
+}
+
+// `rgb(255, 128, 0)`, `rgba(100%, 50%, 0%, 0.5)`, `rgb(255 128 0 / 50%)`, `hsl(30, 100%, 50%)`
+fn color_from_function(function: &str, arguments: &str) -> Option<Color> {
+    let (components, alpha) = match arguments.split_once('/') {
+        Some((components, alpha)) => (components, Some(alpha.trim())),
+        None => (arguments, None),
+    };
+
+    let mut components: Vec<&str> = components
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|component| !component.is_empty())
+        .collect();
+
+    let alpha = match (alpha, components.len()) {
+        (Some(alpha), 3) => Some(alpha),
+        (None, 4) => components.pop(),
+        (None, 3) => None,
+        _ => return None,
+    };
+
+    let alpha = match alpha {
+        Some(alpha) => (parse_fraction(alpha, 1.0)? * 255.0).round() as u8,
+        None => 255,
+    };
+
+    match function {
+        "rgb" | "rgba" => {
+            let r = (parse_fraction(components[0], 255.0)? * 255.0).round() as u8;
+            let g = (parse_fraction(components[1], 255.0)? * 255.0).round() as u8;
+            let b = (parse_fraction(components[2], 255.0)? * 255.0).round() as u8;
+
+            Some(Color::RGBA(r, g, b, alpha))
+        }
+        "hsl" | "hsla" => {
+            let hue: f64 = components[0].trim_end_matches("deg").parse().ok()?;
+            let saturation = parse_fraction(components[1], 100.0)?;
+            let lightness = parse_fraction(components[2], 100.0)?;
+
+            let (r, g, b) = hsl_to_rgb(hue.rem_euclid(360.0), saturation, lightness);
+
+            Some(Color::RGBA(r, g, b, alpha))
+        }
+        _ => None,
+    }
+}
 
-#[derive(Debug, Clone)]
-pub struct FontFetchError;
+// Parses a number or a percentage into the range 0 to 1, `max` being the value of a plain number
+// that maps to 1
+fn parse_fraction(value: &str, max: f64) -> Option<f64> {
+    let fraction = match value.strip_suffix('%') {
+        Some(percentage) => percentage.parse::<f64>().ok()? / 100.0,
+        None => value.parse::<f64>().ok()? / max,
+    };
 
-pub fn color_from_hex(hex: &str) -> Result<Color, ColorParseError> {
-    if hex.len() != 7 || hex.chars().nth(0).unwrap() != '#' {
-        return Err(ColorParseError);
+    if fraction.is_finite() {
+        Some(fraction.clamp(0.0, 1.0))
+    } else {
+        None
     }
+}
+
+fn hsl_to_rgb(hue: f64, saturation: f64, lightness: f64) -> (u8, u8, u8) {
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let x = chroma * (1.0 - ((hue / 60.0) % 2.0 - 1.0).abs());
+    let m = lightness - chroma / 2.0;
 
-    let r = u8::from_str_radix(&hex[1..3], 16).or(Err(ColorParseError))?;
-    let g = u8::from_str_radix(&hex[3..5], 16).or(Err(ColorParseError))?;
-    let b = u8::from_str_radix(&hex[5..7], 16).or(Err(ColorParseError))?;
+    let (r, g, b) = match hue as u16 {
+        0..=59 => (chroma, x, 0.0),
+        60..=119 => (x, chroma, 0.0),
+        120..=179 => (0.0, chroma, x),
+        180..=239 => (0.0, x, chroma),
+        240..=299 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+
+    let channel = |value: f64| ((value + m) * 255.0).round() as u8;
+
+    (channel(r), channel(g), channel(b))
+}
 
-    Ok(Color::RGB(r, g, b))
+pub fn parse_color(value: &str, argument: &str) -> Result<Color, RunnerError> {
+    color_from_str(value).ok_or_else(|| RunnerError::InvalidColor {
+        value: value.to_string(),
+        argument: argument.to_string(),
+    })
 }
 
-pub fn get_font_path(font_name: String) -> Result<String, FontFetchError> {
-    let fc = Fontconfig::new().ok_or(FontFetchError)?;
+pub fn get_font_path(font_name: String) -> Result<String, RunnerError> {
+    let not_found = || RunnerError::FontNotFound {
+        family: font_name.clone(),
+    };
 
-    let font = fc.find(&font_name, None).ok_or(FontFetchError)?;
+    let fc = Fontconfig::new().ok_or_else(not_found)?;
+
+    let font = fc.find(&font_name, None).ok_or_else(not_found)?;
 
     let font_path = font
         .path
         .into_os_string()
         .into_string()
-        .or(Err(FontFetchError))?;
+        .map_err(|_| not_found())?;
 
     Ok(font_path)
 }
+
+// An XDG base directory like `$XDG_CACHE_HOME`, or its default in the home directory if the
+// variable isn't set or is empty
+pub fn xdg_dir(var: &str, default: &str) -> Option<PathBuf> {
+    match env::var_os(var) {
+        Some(dir) if !dir.is_empty() => Some(PathBuf::from(dir)),
+        _ => Some(home::home_dir()?.join(default)),
+    }
+}
+
+// Whether the metadata is of a file with an exec bit set
+pub fn is_executable(metadata: &Metadata) -> bool {
+    metadata.is_file() && metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_hex_colors() {
+        assert_eq!(color_from_str("#fff"), Some(Color::RGB(255, 255, 255)));
+        assert_eq!(color_from_str("#F008"), Some(Color::RGBA(255, 0, 0, 0x88)));
+        assert_eq!(
+            color_from_str("#336699"),
+            Some(Color::RGB(0x33, 0x66, 0x99))
+        );
+        assert_eq!(
+            color_from_str(" #33669980 "),
+            Some(Color::RGBA(0x33, 0x66, 0x99, 0x80))
+        );
+
+        for invalid in ["#", "#12", "#12345", "#1234567", "#ggg", "#ffé"] {
+            assert_eq!(color_from_str(invalid), None, "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn parses_rgb_functions() {
+        assert_eq!(
+            color_from_str("rgb(255, 128, 0)"),
+            Some(Color::RGB(255, 128, 0))
+        );
+        assert_eq!(
+            color_from_str("RGBA(100%, 50%, 0%, 0.5)"),
+            Some(Color::RGBA(255, 128, 0, 128))
+        );
+        assert_eq!(
+            color_from_str("rgb(0 128 255 / 25%)"),
+            Some(Color::RGBA(0, 128, 255, 64))
+        );
+        // out of range values are clamped like in CSS
+        assert_eq!(
+            color_from_str("rgb(300, -5, 0)"),
+            Some(Color::RGB(255, 0, 0))
+        );
+
+        for invalid in [
+            "rgb(1, 2)",
+            "rgb(1, 2, 3, 4, 5)",
+            "rgb(a, b, c)",
+            "rgb(1 2 3 4 / 5)",
+        ] {
+            assert_eq!(color_from_str(invalid), None, "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn parses_hsl_functions() {
+        assert_eq!(
+            color_from_str("hsl(120, 100%, 50%)"),
+            Some(Color::RGB(0, 255, 0))
+        );
+        assert_eq!(
+            color_from_str("hsl(0 0% 50%)"),
+            Some(Color::RGB(128, 128, 128))
+        );
+        assert_eq!(
+            color_from_str("hsla(-120deg, 100%, 50%, 0)"),
+            Some(Color::RGBA(0, 0, 255, 0))
+        );
+        assert_eq!(
+            color_from_str("hsl(240, 100%, 50% / 50%)"),
+            Some(Color::RGBA(0, 0, 255, 128))
+        );
+        assert_eq!(color_from_str("hsv(0, 0%, 0%)"), None);
+    }
+
+    #[test]
+    fn parses_color_names() {
+        assert_eq!(color_from_str("Red"), Some(Color::RGB(255, 0, 0)));
+        assert_eq!(color_from_str("transparent"), Some(Color::RGBA(0, 0, 0, 0)));
+        assert_eq!(color_from_str("rebeccapurple"), None);
+    }
+}